@@ -58,12 +58,42 @@ fn generate_zeroes(size: usize) -> Vec<u8> {
     vec![0u8; size]
 }
 
+/// Generates a vector alternating zero-filled 4KB blocks with text blocks, each
+/// holding a 512-byte zero run.
+///
+/// This represents sparse data such as VM disk images and NTFS volumes, where most
+/// blocks are zero-filled.
+///
+/// # Parameters
+/// * `size` - The target size in bytes.
+///
+/// # Returns
+/// A `Vec<u8>` with three zero blocks for every text block, truncated to `size`.
+fn generate_sparse(size: usize) -> Vec<u8> {
+    let text = generate_text(4096);
+    let mut vec = Vec::with_capacity(size);
+    let mut block = 0;
+    while vec.len() < size {
+        if block % 4 == 0 {
+            vec.extend_from_slice(&text[..2048]);
+            vec.extend_from_slice(&[0u8; 512]);
+            vec.extend_from_slice(&text[2048..3584]);
+        } else {
+            vec.extend_from_slice(&[0u8; 4096]);
+        }
+        block += 1;
+    }
+    vec.truncate(size);
+    vec
+}
+
 /// Benchmarks the LZNT1 compression algorithm against various data patterns.
 ///
 /// Scenarios:
 /// 1. **Zeroes**: High repetition, RLE-friendly.
 /// 2. **Random**: High entropy, generally incompressible.
 /// 3. **Text**: Moderate entropy, representative of real-world text.
+/// 4. **Sparse**: Mostly zero-filled blocks, representative of disk images.
 fn bench_compression(c: &mut Criterion) {
     let mut group = c.benchmark_group("LZNT1 Compression");
 
@@ -74,6 +104,7 @@ fn bench_compression(c: &mut Criterion) {
         ("Zeroes", generate_zeroes(size)),
         ("Random", generate_random(size)),
         ("Text", generate_text(size)),
+        ("Sparse", generate_sparse(size)),
     ];
 
     for (name, input_data) in &scenarios {
@@ -106,6 +137,7 @@ fn bench_decompression(c: &mut Criterion) {
         ("Zeroes", generate_zeroes(size)),
         ("Random", generate_random(size)),
         ("Text", generate_text(size)),
+        ("Sparse", generate_sparse(size)),
     ];
 
     for (name, source_data) in &scenarios {
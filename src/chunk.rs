@@ -0,0 +1,230 @@
+//! Chunk-level access to LZNT1 streams.
+//!
+//! An LZNT1 stream is a sequence of independent chunks, each made of a 2-byte
+//! header followed by a body of up to 4096 bytes. [`chunks`] walks the headers
+//! without decoding anything, so callers can inspect, skip or parallelize chunks
+//! and decode them individually with [`Chunk::decompress_into`].
+
+use crate::decompress::{ChunkLocation, decompress_chunk};
+use crate::error::{DecompressionError, ErrorPosition};
+
+type Result<T> = core::result::Result<T, DecompressionError>;
+
+/// Bitmask to extract the chunk size (lower 12 bits) from the header.
+const HEADER_SIZE_MASK: u16 = 0x0FFF;
+
+/// Position of the signature bits (12-14) within the header.
+const HEADER_SIGNATURE_SHIFT: u32 = 12;
+
+/// Expected signature bits (0b011) of every chunk header.
+const HEADER_SIGNATURE: u8 = 0b011;
+
+/// Bit flag indicating if the chunk is compressed (0xBxxx) or raw (0x3xxx).
+/// LZNT1 typically uses the MSB or specific high nibbles, but checking 0x8000 is sufficient.
+const HEADER_COMPRESSED_FLAG: u16 = 0x8000;
+
+/// A decoded 2-byte chunk header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChunkHeader {
+    /// Whether the body is LZNT1-compressed (`0xBxxx`) or stored raw (`0x3xxx`).
+    pub compressed: bool,
+    /// Signature bits 12-14, which read `0b011` in well-formed streams.
+    pub signature: u8,
+    /// Length of the chunk body in bytes (1 to 4096).
+    pub body_len: usize,
+}
+
+impl ChunkHeader {
+    /// Size of an encoded chunk header in bytes.
+    pub const SIZE: usize = 2;
+
+    /// Decodes a little-endian header value.
+    ///
+    /// Returns `None` for `0x0000`, the end-of-stream marker.
+    #[must_use]
+    pub const fn parse(header: u16) -> Option<Self> {
+        if header == 0 {
+            return None;
+        }
+        Some(Self {
+            compressed: (header & HEADER_COMPRESSED_FLAG) != 0,
+            signature: ((header >> HEADER_SIGNATURE_SHIFT) & 0b111) as u8,
+            body_len: ((header & HEADER_SIZE_MASK) + 1) as usize,
+        })
+    }
+
+    /// Returns `true` if the signature bits read `0b011`.
+    #[must_use]
+    pub const fn has_valid_signature(&self) -> bool {
+        self.signature == HEADER_SIGNATURE
+    }
+}
+
+/// A single chunk of an LZNT1 stream, as yielded by [`chunks`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chunk<'a> {
+    /// The decoded chunk header.
+    pub header: ChunkHeader,
+    /// The chunk body (`header.body_len` bytes).
+    pub body: &'a [u8],
+    /// Offset of the chunk header in the input.
+    pub offset: usize,
+    /// Zero-based index of the chunk in the stream.
+    pub index: usize,
+}
+
+impl Chunk<'_> {
+    /// Decompresses the chunk into the start of `output`, returning the number of
+    /// bytes written.
+    ///
+    /// Back-references are resolved within the chunk, and a chunk expanding beyond
+    /// 4096 bytes fails with [`DecompressionError::ChunkOverflow`]. An `output`
+    /// of 4096 bytes is always large enough for a valid chunk; a smaller one may
+    /// fail with [`DecompressionError::BufferTooSmall`]. Error positions refer to
+    /// the whole input, with the chunk output offset reported as 0.
+    pub fn decompress_into(&self, output: &mut [u8]) -> Result<usize> {
+        decompress_chunk(self.body, self.header.compressed, output, self.location(0))
+    }
+
+    /// Returns the location of the chunk for error reporting, given the number of
+    /// bytes produced before it.
+    pub(crate) const fn location(&self, output_offset: usize) -> ChunkLocation {
+        ChunkLocation {
+            body_offset: self.offset + ChunkHeader::SIZE,
+            index: self.index,
+            output_offset,
+        }
+    }
+}
+
+/// Returns an iterator over the chunks of an LZNT1 stream.
+///
+/// Iteration ends at the end of the input, at a `0x0000` end-of-stream marker or
+/// at a single trailing null byte. A truncated header or body is reported as an
+/// error, after which the iterator yields nothing more.
+///
+/// # Example
+///
+/// ```rust
+/// use lznt1::{chunks, compress};
+///
+/// let mut compressed = Vec::new();
+/// compress(&[0u8; 10000], &mut compressed);
+///
+/// let mut total = 0;
+/// let mut buf = [0u8; 4096];
+/// for chunk in chunks(&compressed) {
+///     total += chunk.unwrap().decompress_into(&mut buf).unwrap();
+/// }
+/// assert_eq!(total, 10000);
+/// ```
+#[must_use]
+pub const fn chunks(input: &[u8]) -> Chunks<'_> {
+    Chunks {
+        input,
+        pos: 0,
+        index: 0,
+        done: false,
+        terminated: false,
+    }
+}
+
+/// Iterator over the chunks of an LZNT1 stream, created by [`chunks`].
+#[derive(Debug, Clone)]
+pub struct Chunks<'a> {
+    input: &'a [u8],
+    pos: usize,
+    index: usize,
+    done: bool,
+    terminated: bool,
+}
+
+impl<'a> Chunks<'a> {
+    /// Returns the input offset just past the last chunk or end-of-stream marker
+    /// consumed.
+    #[must_use]
+    pub const fn offset(&self) -> usize {
+        self.pos
+    }
+
+    /// Returns `true` once a `0x0000` end-of-stream marker or a single trailing
+    /// null byte has been consumed.
+    #[must_use]
+    pub const fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+
+    /// Returns `true` if another chunk starts at the current offset, rather than
+    /// the end of the input or an end-of-stream marker.
+    #[must_use]
+    pub fn has_next_chunk(&self) -> bool {
+        !self.done && !matches!(&self.input[self.pos..], [] | [0] | [0, 0, ..])
+    }
+
+    /// Parses the next chunk, advancing past it.
+    fn parse_next(&mut self) -> Result<Option<Chunk<'a>>> {
+        let input = self.input;
+        let end = input.len();
+        let offset = self.pos;
+        let position = ErrorPosition {
+            input_offset: offset,
+            chunk_index: self.index,
+            chunk_output_offset: 0,
+        };
+
+        // LZNT1 streams may be null-terminated (single 0x00 byte at EOF).
+        if offset == end {
+            return Ok(None);
+        }
+        if offset + 1 == end && input[offset] == 0 {
+            self.pos = end;
+            self.terminated = true;
+            return Ok(None);
+        }
+
+        // Ensure we can read the 2-byte header.
+        if offset + ChunkHeader::SIZE > end {
+            return Err(DecompressionError::UnexpectedEof { position });
+        }
+
+        let raw = u16::from_le_bytes([input[offset], input[offset + 1]]);
+        let body_start = offset + ChunkHeader::SIZE;
+
+        let Some(header) = ChunkHeader::parse(raw) else {
+            self.pos = body_start;
+            self.terminated = true;
+            return Ok(None); // Standard End-of-Stream marker
+        };
+
+        // Ensure the chunk body is within bounds.
+        if body_start + header.body_len > end {
+            return Err(DecompressionError::InputTooShort { position });
+        }
+
+        self.pos = body_start + header.body_len;
+        self.index += 1;
+        Ok(Some(Chunk {
+            header,
+            body: &input[body_start..self.pos],
+            offset,
+            index: position.chunk_index,
+        }))
+    }
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = Result<Chunk<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = self.parse_next().transpose();
+        if !matches!(next, Some(Ok(_))) {
+            self.done = true;
+        }
+        next
+    }
+}
+
+impl core::iter::FusedIterator for Chunks<'_> {}
@@ -1,4 +1,11 @@
+use alloc::boxed::Box;
 use alloc::vec::Vec;
+use core::borrow::BorrowMut;
+use core::mem::size_of;
+
+use crate::error::{CompressionError, SinkFull};
+use crate::report::MatchStats;
+use crate::sink::{CountingSink, OutputSink, SliceSink};
 
 /// Standard chunk size for LZNT1 compression (4KB).
 const CHUNK_SIZE: usize = 4096;
@@ -9,16 +16,36 @@ const MIN_MATCH: usize = 3;
 /// Absolute hard limit for match length (12 bits + 3).
 const MAX_MATCH: usize = 4098;
 
-/// Maximum number of hash chain entries to inspect per position.
+/// Maximum number of hash chain entries to inspect per position at the default level.
 /// Limits worst-case performance to O(N * Depth) rather than O(N^2).
 const MAX_SEARCH_DEPTH: usize = 16;
 
+/// Minimum length of a run of one byte encoded directly as offset-1 tuples,
+/// bypassing the hash chain search.
+const MIN_RUN: usize = 32;
+
+/// Maximum number of positions a match may be deferred by lazy evaluation.
+const MAX_LAZY_STEPS: usize = 2;
+
 /// Hash mask for the 4096-entry table (12 bits).
 const HASH_MASK: usize = 0xFFF;
 
 /// Marker for an empty hash table entry.
 const EMPTY_ENTRY: u16 = 0xFFFF;
 
+/// Cost in bits of a literal (8 bits and its tag bit), used by the optimal parser.
+const LITERAL_COST: u32 = 9;
+
+/// Cost in bits of a match tuple (16 bits and its tag bit), used by the optimal parser.
+const TUPLE_COST: u32 = 17;
+
+/// Number of leaves of the optimal parser's segment tree, covering every position
+/// of a chunk including its end.
+const TREE_LEAVES: usize = 2 * CHUNK_SIZE;
+
+/// End-of-stream marker, a chunk header of zero.
+pub(crate) const END_MARKER: [u8; 2] = [0, 0];
+
 /// Header flags for compressed vs uncompressed chunks.
 const HEADER_COMPRESSED: u16 = 0xB000;
 const HEADER_RAW: u16 = 0x3000;
@@ -45,37 +72,40 @@ impl TagAccumulator {
     }
 
     /// Adds a literal byte to the current group.
-    fn push_literal(&mut self, byte: u8, output: &mut Vec<u8>) {
+    fn push_literal(&mut self, byte: u8, output: &mut ChunkBuffer) {
         // Tag bit 0 is implicit (do nothing to tag_byte)
         self.buffer[self.buffer_len] = byte;
         self.buffer_len += 1;
+        output.stats.record_literal();
         self.commit_item(output);
     }
 
-    /// Adds a compressed tuple (offset/length pair) to the current group.
-    fn push_tuple(&mut self, tuple: u16, output: &mut Vec<u8>) {
+    /// Adds a compressed tuple (offset/length pair) to the current group, encoded
+    /// with a length field of `split` bits.
+    fn push_tuple(&mut self, offset: usize, length: usize, split: usize, output: &mut ChunkBuffer) {
         // Set tag bit to 1 at the current item index
         self.tag_byte |= 1 << self.item_count;
 
         // Write 2-byte tuple (Little Endian)
-        let bytes = tuple.to_le_bytes();
+        let bytes = encode_tuple(offset, length, split).to_le_bytes();
         self.buffer[self.buffer_len] = bytes[0];
         self.buffer[self.buffer_len + 1] = bytes[1];
         self.buffer_len += 2;
+        output.stats.record_tuple(offset, length);
 
         self.commit_item(output);
     }
 
     /// Increments the item count and flushes the group if full (8 items).
-    fn commit_item(&mut self, output: &mut Vec<u8>) {
+    fn commit_item(&mut self, output: &mut ChunkBuffer) {
         self.item_count += 1;
         if self.item_count == 8 {
             self.flush(output);
         }
     }
 
-    /// Writes the current tag group to the output vector and resets state.
-    fn flush(&mut self, output: &mut Vec<u8>) {
+    /// Writes the current tag group to the chunk buffer and resets state.
+    fn flush(&mut self, output: &mut ChunkBuffer) {
         if self.item_count > 0 {
             output.push(self.tag_byte);
             output.extend_from_slice(&self.buffer[..self.buffer_len]);
@@ -87,7 +117,70 @@ impl TagAccumulator {
     }
 }
 
+/// Bounded buffer receiving the compressed body of a single chunk.
+///
+/// A chunk header describes bodies of up to `CHUNK_SIZE` bytes, which is the
+/// capacity. Writes beyond it mark the buffer as overflowed, telling the caller to
+/// store the chunk raw instead. Otherwise the body is kept if it holds at most
+/// `CompressOptions::body_limit` bytes, which is the full capacity with
+/// `RawChunks::Never`, or with `min_savings(0)` on a full chunk.
+///
+/// The items of the body are counted as they are written, for
+/// [`compress_with_report`](crate::compress_with_report).
+struct ChunkBuffer {
+    data: [u8; CHUNK_SIZE],
+    len: usize,
+    overflowed: bool,
+    stats: MatchStats,
+}
+
+impl ChunkBuffer {
+    const fn new() -> Self {
+        Self {
+            data: [0; CHUNK_SIZE],
+            len: 0,
+            overflowed: false,
+            stats: MatchStats::EMPTY,
+        }
+    }
+
+    /// Empties the buffer for a new chunk.
+    const fn clear(&mut self) {
+        self.len = 0;
+        self.overflowed = false;
+        self.stats = MatchStats::EMPTY;
+    }
+
+    /// Appends a single byte.
+    fn push(&mut self, byte: u8) {
+        self.extend_from_slice(&[byte]);
+    }
+
+    /// Appends a slice of bytes, or marks the buffer as overflowed if they do not fit.
+    fn extend_from_slice(&mut self, bytes: &[u8]) {
+        if bytes.len() > CHUNK_SIZE - self.len {
+            self.overflowed = true;
+            return;
+        }
+        self.data[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    /// Returns the buffer if it holds a complete body of at most `limit` bytes.
+    fn kept(&self, limit: usize) -> Option<&Self> {
+        (!self.overflowed && self.len <= limit).then_some(self)
+    }
+
+    /// Returns the compressed body.
+    fn body(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+}
+
 /// Context to hold reusable memory for compression to avoid allocation churn.
+///
+/// Holds the hash chains of the match finder (16KB). [`compress_into`] borrows one
+/// so that it needs neither a heap allocation nor a large stack frame.
 pub struct Lznt1Context {
     // Maps a 3-byte hash to the *most recent* position in the chunk.
     head: [u16; CHUNK_SIZE],
@@ -102,6 +195,7 @@ impl Default for Lznt1Context {
 }
 
 impl Lznt1Context {
+    /// Creates an empty context.
     #[must_use]
     pub const fn new() -> Self {
         Self {
@@ -127,6 +221,217 @@ impl Lznt1Context {
             self.head[h] = idx as u16;
         }
     }
+
+    /// Updates the hash chains for every position from `*inserted` up to `end`.
+    ///
+    /// Tracking the inserted positions ensures none is inserted twice, which would
+    /// link it to itself.
+    fn insert_until(&mut self, input: &[u8], inserted: &mut usize, end: usize) {
+        while *inserted < end {
+            self.update(input, *inserted);
+            *inserted += 1;
+        }
+    }
+}
+
+/// Options controlling the speed and ratio of compression.
+///
+/// Every level produces valid LZNT1 streams; they only differ in how hard the
+/// encoder searches for matches. [`CompressOptions::new`] is the default level
+/// used by [`compress`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressOptions {
+    max_chain: usize,
+    nice_length: usize,
+    insert_matched: bool,
+    lazy_steps: usize,
+    optimal: bool,
+    min_savings: usize,
+    raw_chunks: RawChunks,
+    pub(crate) end_marker: bool,
+}
+
+impl Default for CompressOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CompressOptions {
+    /// Creates the default options, balancing speed and ratio.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_chain: MAX_SEARCH_DEPTH,
+            nice_length: MAX_MATCH,
+            insert_matched: true,
+            lazy_steps: 0,
+            optimal: false,
+            min_savings: 1,
+            raw_chunks: RawChunks::Auto,
+            end_marker: false,
+        }
+    }
+
+    /// Options favoring speed: short hash chain searches, which stop at the first
+    /// reasonably long match, and no hash updates inside matches.
+    #[must_use]
+    pub const fn fastest() -> Self {
+        Self {
+            max_chain: 2,
+            nice_length: 32,
+            insert_matched: false,
+            lazy_steps: 0,
+            optimal: false,
+            min_savings: 1,
+            raw_chunks: RawChunks::Auto,
+            end_marker: false,
+        }
+    }
+
+    /// Options between the default and best levels: two-step lazy matching, for
+    /// most of the ratio of an optimal parse at a fraction of its cost.
+    #[must_use]
+    pub const fn better() -> Self {
+        Self::new().max_chain(32).nice_length(256).lazy(2)
+    }
+
+    /// Options favoring ratio: long hash chain searches with two-step lazy matching.
+    #[must_use]
+    pub const fn best() -> Self {
+        Self {
+            max_chain: 512,
+            nice_length: MAX_MATCH,
+            insert_matched: true,
+            lazy_steps: MAX_LAZY_STEPS,
+            optimal: false,
+            min_savings: 1,
+            raw_chunks: RawChunks::Auto,
+            end_marker: false,
+        }
+    }
+
+    /// Options for the highest ratio, like `COMPRESSION_ENGINE_MAXIMUM` on Windows:
+    /// long hash chain searches and an optimal parse of every chunk.
+    ///
+    /// This is an order of magnitude slower than [`CompressOptions::best`].
+    #[must_use]
+    pub const fn maximum() -> Self {
+        Self::best().optimal(true)
+    }
+
+    /// Sets the maximum number of hash chain entries inspected per position.
+    ///
+    /// Values below 1 are treated as 1.
+    #[must_use]
+    pub const fn max_chain(mut self, depth: usize) -> Self {
+        self.max_chain = if depth == 0 { 1 } else { depth };
+        self
+    }
+
+    /// Sets the match length at which the search stops looking for longer matches.
+    #[must_use]
+    pub const fn nice_length(mut self, length: usize) -> Self {
+        self.nice_length = length;
+        self
+    }
+
+    /// Sets whether every position covered by a match is inserted into the hash
+    /// chains (enabled by default), rather than only its first position.
+    ///
+    /// Skipping the insertions is faster, but hides later matches starting
+    /// inside earlier ones.
+    #[must_use]
+    pub const fn insert_matched(mut self, insert: bool) -> Self {
+        self.insert_matched = insert;
+        self
+    }
+
+    /// Sets the number of positions a match may be deferred by lazy evaluation
+    /// (0 by default, at most 2).
+    ///
+    /// Before committing to a match shorter than the nice length, the encoder looks
+    /// for matches starting up to `steps` bytes later, as zlib does, and emits
+    /// literals first if one of them is longer than the current choice by at least
+    /// the number of extra literals it needs: a match one byte later must be
+    /// strictly longer.
+    #[must_use]
+    pub const fn lazy(mut self, steps: usize) -> Self {
+        self.lazy_steps = if steps > MAX_LAZY_STEPS {
+            MAX_LAZY_STEPS
+        } else {
+            steps
+        };
+        self
+    }
+
+    /// Enables or disables the optimal parse (disabled by default).
+    ///
+    /// Greedy matching takes the longest match at each position, but the
+    /// offset/length split of LZNT1 tuples narrows as a chunk grows, so a locally
+    /// longest match is not always best. The optimal parse finds the cheapest
+    /// sequence of literals and matches for each chunk with a shortest-path
+    /// search that models the tag byte overhead and the split thresholds. The
+    /// greedy encoding is computed as well and kept whenever the optimal parse
+    /// does not beat it, so a chunk never grows.
+    #[must_use]
+    pub const fn optimal(mut self, optimal: bool) -> Self {
+        self.optimal = optimal;
+        self
+    }
+
+    /// Sets the minimum number of bytes the compressed form of a chunk must save
+    /// over its raw copy to be kept with [`RawChunks::Auto`] (1 by default).
+    ///
+    /// With 0, a chunk is stored compressed even when that saves nothing.
+    #[must_use]
+    pub const fn min_savings(mut self, bytes: usize) -> Self {
+        self.min_savings = bytes;
+        self
+    }
+
+    /// Sets when chunks are stored raw ([`RawChunks::Auto`] by default).
+    #[must_use]
+    pub const fn raw_chunks(mut self, raw_chunks: RawChunks) -> Self {
+        self.raw_chunks = raw_chunks;
+        self
+    }
+
+    /// Sets whether the `0x0000` end-of-stream marker is appended after the last
+    /// chunk (disabled by default).
+    #[must_use]
+    pub const fn end_marker(mut self, enabled: bool) -> Self {
+        self.end_marker = enabled;
+        self
+    }
+
+    /// Returns the largest compressed body worth keeping for a chunk of
+    /// `chunk_len` bytes, or `None` if the chunk is to be stored raw.
+    const fn body_limit(&self, chunk_len: usize) -> Option<usize> {
+        match self.raw_chunks {
+            RawChunks::Auto => chunk_len.checked_sub(self.min_savings),
+            RawChunks::Never => Some(CHUNK_SIZE),
+            RawChunks::Always => None,
+        }
+    }
+}
+
+/// When the encoder stores chunks raw rather than compressed.
+///
+/// Only the final chunk of a stream can be shorter than 4096 bytes, so every other
+/// raw chunk holds exactly 4096 bytes, as NTFS expects, whatever the policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RawChunks {
+    /// Store a chunk raw when compressing it does not save at least
+    /// [`min_savings`](CompressOptions::min_savings) bytes.
+    #[default]
+    Auto,
+    /// Store a chunk compressed whenever its compressed body fits in 4096 bytes,
+    /// even if it is larger than the raw copy. Such chunks are rejected by
+    /// [`ValidationLevel::Strict`](crate::ValidationLevel::Strict).
+    Never,
+    /// Store every chunk raw, without compressing it.
+    Always,
 }
 
 /// Compresses the entire input into the output vector using the LZNT1 algorithm.
@@ -138,140 +443,691 @@ impl Lznt1Context {
 /// * `input`: The source data to compress.
 /// * `output`: The destination vector (appended to).
 pub fn compress(input: &[u8], output: &mut Vec<u8>) {
-    let mut ctx = Lznt1Context::new();
-    let mut src_pos = 0;
+    compress_with(input, output, &CompressOptions::new());
+}
+
+/// Compresses the entire input into the output vector using the given
+/// [`CompressOptions`].
+///
+/// # Example
+///
+/// ```rust
+/// use lznt1::{CompressOptions, compress_with, decompress};
+///
+/// let input = b"abcabcabcabc abcabcabcabc abcabcabcabc".repeat(100);
+/// let mut compressed = Vec::new();
+/// compress_with(&input, &mut compressed, &CompressOptions::best());
+///
+/// let mut output = Vec::new();
+/// decompress(&compressed, &mut output).unwrap();
+/// assert_eq!(output, input);
+/// ```
+pub fn compress_with(input: &[u8], output: &mut Vec<u8>, options: &CompressOptions) {
+    // Vectors grow as needed, so writing to one never fails.
+    let _ = compress_to_sink(input, output, options);
+}
+
+/// Compresses the entire input into any [`OutputSink`] using the given
+/// [`CompressOptions`].
+///
+/// Produces the same bytes as [`compress_with`]. Each chunk is compressed into an
+/// internal buffer first, so only complete chunks (header and body) are written
+/// to `output`.
+///
+/// # Errors
+///
+/// Returns [`SinkFull`] if `output` runs out of space. The chunks written before
+/// the failure are left in place.
+pub fn compress_to_sink<S: OutputSink + ?Sized>(
+    input: &[u8],
+    output: &mut S,
+    options: &CompressOptions,
+) -> Result<(), SinkFull> {
+    let mut scratch = ChunkScratch::new(options);
+    compress_chunks(
+        input,
+        output,
+        &mut Lznt1Context::new(),
+        &mut scratch,
+        options,
+    )
+}
+
+/// Compresses the entire input into a fixed buffer, without allocating, like
+/// `RtlCompressBuffer`.
+///
+/// Produces the same bytes as [`compress`], using `ctx` as scratch memory, and
+/// returns the number of bytes written to the start of `output`.
+///
+/// # Errors
+///
+/// Returns [`CompressionError::BufferTooSmall`] if the compressed data does not fit
+/// in `output`. The required size is then computed by compressing the input again
+/// without storing it, and the contents of `output` are unspecified.
+///
+/// # Example
+///
+/// ```rust
+/// use lznt1::{CompressionError, Lznt1Context, compress_into, decompress};
+///
+/// let input = b"abcabcabcabc abcabcabcabc abcabcabcabc".repeat(100);
+/// let mut ctx = Lznt1Context::new();
+///
+/// let mut small = [0u8; 16];
+/// let Err(CompressionError::BufferTooSmall { required }) =
+///     compress_into(&input, &mut small, &mut ctx)
+/// else {
+///     panic!("expected BufferTooSmall");
+/// };
+///
+/// let mut buffer = vec![0u8; required];
+/// let written = compress_into(&input, &mut buffer, &mut ctx).unwrap();
+/// assert_eq!(written, required);
+///
+/// let mut output = Vec::new();
+/// decompress(&buffer[..written], &mut output).unwrap();
+/// assert_eq!(output, input);
+/// ```
+pub fn compress_into(
+    input: &[u8],
+    output: &mut [u8],
+    ctx: &mut Lznt1Context,
+) -> Result<usize, CompressionError> {
+    let options = CompressOptions::new();
+    let mut scratch = ChunkScratch::new(&options);
+    compress_into_buffer(input, output, ctx, &mut scratch, &options)
+}
+
+/// A reusable compressor owning its scratch memory and options.
+///
+/// [`compress`] and [`compress_with`] build a fresh 16KB [`Lznt1Context`] on the
+/// stack for every call. A `Compressor` keeps its context, on the heap by default,
+/// so compressing many small buffers repeats no setup and works on threads with
+/// small stacks. The context can also be stored inline or borrowed through
+/// [`with_context`](Self::with_context).
+///
+/// # Example
+///
+/// ```rust
+/// use lznt1::{CompressOptions, Compressor, decompress};
+///
+/// let mut compressor = Compressor::with_options(CompressOptions::best());
+/// for message in [&b"first message, first message"[..], b"second message"] {
+///     let mut compressed = Vec::new();
+///     compressor.compress(message, &mut compressed);
+///
+///     let mut output = Vec::new();
+///     decompress(&compressed, &mut output).unwrap();
+///     assert_eq!(output, message);
+/// }
+/// ```
+pub struct Compressor<C = Box<Lznt1Context>> {
+    ctx: C,
+    scratch: ChunkScratch,
+    options: CompressOptions,
+}
+
+impl Default for Compressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Compressor {
+    /// Creates a compressor with the default options and a heap-allocated context.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_options(CompressOptions::new())
+    }
+
+    /// Creates a compressor with the given options and a heap-allocated context.
+    #[must_use]
+    pub fn with_options(options: CompressOptions) -> Self {
+        Self::with_context(Box::default(), options)
+    }
+
+    /// Returns the number of bytes of scratch memory used by a compressor with the
+    /// given options, like `RtlGetCompressionWorkSpaceSize`.
+    ///
+    /// This covers the context, the chunk buffers and, for the optimal parse, the
+    /// parser tables.
+    #[must_use]
+    pub const fn workspace_size(options: &CompressOptions) -> usize {
+        let mut size = size_of::<Lznt1Context>() + size_of::<ChunkBuffer>();
+        if options.optimal {
+            size += size_of::<ChunkBuffer>() + OptimalParser::HEAP_SIZE;
+        }
+        size
+    }
+}
 
-    while src_pos < input.len() {
-        let chunk_len = (input.len() - src_pos).min(CHUNK_SIZE);
-        let chunk = &input[src_pos..src_pos + chunk_len];
+impl<C: BorrowMut<Lznt1Context>> Compressor<C> {
+    /// Creates a compressor using `ctx` as its context, which may be an inline
+    /// [`Lznt1Context`], a [`Box`] or a mutable reference.
+    pub fn with_context(ctx: C, options: CompressOptions) -> Self {
+        Self {
+            ctx,
+            scratch: ChunkScratch::new(&options),
+            options,
+        }
+    }
 
-        let start_out = output.len();
-        // Reserve space for Header (2 bytes)
-        output.extend_from_slice(&[0, 0]);
+    /// Returns the options used by this compressor.
+    pub const fn options(&self) -> &CompressOptions {
+        &self.options
+    }
 
-        compress_chunk(chunk, output, &mut ctx);
+    /// Compresses the entire input, appending it to `output`.
+    ///
+    /// Produces the same bytes as [`compress_with`] with this compressor's options.
+    pub fn compress(&mut self, input: &[u8], output: &mut Vec<u8>) {
+        // Vectors grow as needed, so writing to one never fails.
+        let _ = compress_chunks(
+            input,
+            output,
+            self.ctx.borrow_mut(),
+            &mut self.scratch,
+            &self.options,
+        );
+    }
 
-        let compressed_len = output.len() - start_out - 2;
+    /// Compresses the entire input into a fixed buffer, returning the number of
+    /// bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CompressionError::BufferTooSmall`] if the compressed data does not
+    /// fit in `output`, as [`compress_into`] does.
+    pub fn compress_into(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<usize, CompressionError> {
+        compress_into_buffer(
+            input,
+            output,
+            self.ctx.borrow_mut(),
+            &mut self.scratch,
+            &self.options,
+        )
+    }
+}
+
+/// Compresses the entire input into a fixed buffer, computing the required size
+/// if it does not fit.
+fn compress_into_buffer(
+    input: &[u8],
+    output: &mut [u8],
+    ctx: &mut Lznt1Context,
+    scratch: &mut ChunkScratch,
+    options: &CompressOptions,
+) -> Result<usize, CompressionError> {
+    let mut sink = SliceSink::new(output);
+    if compress_chunks(input, &mut sink, ctx, scratch, options).is_ok() {
+        return Ok(sink.len());
+    }
+
+    let mut counter = CountingSink::new();
+    // Counting never fails without a limit.
+    let _ = compress_chunks(input, &mut counter, ctx, scratch, options);
+    Err(CompressionError::BufferTooSmall {
+        required: counter.count(),
+    })
+}
+
+/// Compresses every chunk of the input into `output`, using `ctx` and `scratch`
+/// as scratch memory.
+pub(crate) fn compress_chunks<S: OutputSink + ?Sized>(
+    input: &[u8],
+    output: &mut S,
+    ctx: &mut Lznt1Context,
+    scratch: &mut ChunkScratch,
+    options: &CompressOptions,
+) -> Result<(), SinkFull> {
+    for chunk in input.chunks(CHUNK_SIZE) {
+        let (header, body, _) = scratch.encode(chunk, ctx, options);
+        output.extend_from_slice(&header)?;
+        output.extend_from_slice(body)?;
+    }
+    if options.end_marker {
+        output.extend_from_slice(&END_MARKER)?;
+    }
+
+    Ok(())
+}
+
+/// Buffers receiving the candidate encodings of a chunk.
+pub(crate) struct ChunkScratch {
+    greedy: ChunkBuffer,
+    optimal: Option<(OptimalParser, ChunkBuffer)>,
+}
+
+impl ChunkScratch {
+    /// Creates the buffers needed to compress chunks with `options`.
+    pub(crate) fn new(options: &CompressOptions) -> Self {
+        Self {
+            greedy: ChunkBuffer::new(),
+            optimal: options
+                .optimal
+                .then(|| (OptimalParser::new(), ChunkBuffer::new())),
+        }
+    }
 
-        if compressed_len < chunk.len() {
-            // Success: Overwrite header with Compressed flag + size
-            let header = encode_header(HEADER_COMPRESSED, compressed_len);
-            let h_bytes = header.to_le_bytes();
-            output[start_out] = h_bytes[0];
-            output[start_out + 1] = h_bytes[1];
+    /// Encodes a chunk of at most 4096 bytes, returning its header, its body and
+    /// the items of the body.
+    ///
+    /// The body is the compressed form if the raw chunk policy keeps it, or the
+    /// chunk itself stored raw, with empty statistics.
+    pub(crate) fn encode<'a>(
+        &'a mut self,
+        chunk: &'a [u8],
+        ctx: &mut Lznt1Context,
+        options: &CompressOptions,
+    ) -> ([u8; 2], &'a [u8], MatchStats) {
+        let kept = match options.body_limit(chunk.len()) {
+            None => None,
+            Some(limit) if compress_uniform_chunk(chunk, &mut self.greedy) => {
+                // A literal and a single tuple: no search can do better.
+                self.greedy.kept(limit)
+            }
+            Some(limit) => self.search(chunk, ctx, options, limit),
+        };
+
+        if let Some(buffer) = kept {
+            let header = encode_header(HEADER_COMPRESSED, buffer.len);
+            (header.to_le_bytes(), buffer.body(), buffer.stats)
         } else {
-            // Failure: Expansion or no savings. Revert and store Raw.
-            output.truncate(start_out);
+            // Expansion or not enough savings: store Raw.
             let header = encode_header(HEADER_RAW, chunk.len());
-            output.extend_from_slice(&header.to_le_bytes());
-            output.extend_from_slice(chunk);
+            (header.to_le_bytes(), chunk, MatchStats::EMPTY)
         }
+    }
+
+    /// Compresses a chunk by searching for matches, returning the buffer holding
+    /// the compressed body if it is at most `limit` bytes.
+    fn search<'a>(
+        &'a mut self,
+        chunk: &[u8],
+        ctx: &mut Lznt1Context,
+        options: &CompressOptions,
+        limit: usize,
+    ) -> Option<&'a ChunkBuffer> {
+        compress_chunk(chunk, &mut self.greedy, ctx, options, limit);
+        let mut kept = self.greedy.kept(limit);
+
+        if let Some((parser, parsed)) = &mut self.optimal {
+            parser.compress_chunk(chunk, parsed, ctx, options);
+            // Keep the greedy encoding unless the optimal parse is strictly smaller.
+            if let Some(parsed) = parsed.kept(limit)
+                && kept.is_none_or(|greedy| parsed.len < greedy.len)
+            {
+                kept = Some(parsed);
+            }
+        }
+
+        kept
+    }
+}
+
+/// Compresses a chunk made of a single repeated byte, such as a zero-filled block,
+/// as a literal followed by one offset-1 tuple.
+///
+/// Returns `false`, leaving `output` untouched, if the chunk is not uniform or too
+/// short to hold a tuple.
+fn compress_uniform_chunk(chunk: &[u8], output: &mut ChunkBuffer) -> bool {
+    let [first, rest @ ..] = chunk else {
+        return false;
+    };
+    if rest.len() < MIN_MATCH || rest.iter().any(|b| b != first) {
+        return false;
+    }
+
+    output.clear();
+    let mut accumulator = TagAccumulator::new();
+    accumulator.push_literal(*first, output);
+    // A chunk holds at most 4096 bytes and the split at position 1 allows tuples
+    // of up to 4098 bytes, so one tuple covers the rest of the chunk.
+    accumulator.push_tuple(1, rest.len(), split_at(1), output);
+    accumulator.flush(output);
+    true
+}
 
-        src_pos += chunk_len;
+/// Returns the length of the run of `chunk[in_idx - 1]` starting at `in_idx`.
+fn run_length(chunk: &[u8], in_idx: usize) -> usize {
+    if in_idx == 0 || chunk[in_idx] != chunk[in_idx - 1] {
+        return 0;
     }
+    let byte = chunk[in_idx - 1];
+    chunk[in_idx..].iter().take_while(|&&b| b == byte).count()
 }
 
 /// Compresses a single chunk (max 4096 bytes).
-fn compress_chunk(chunk: &[u8], output: &mut Vec<u8>, ctx: &mut Lznt1Context) {
+///
+/// Stops early once the compressed body exceeds `limit` bytes.
+fn compress_chunk(
+    chunk: &[u8],
+    output: &mut ChunkBuffer,
+    ctx: &mut Lznt1Context,
+    options: &CompressOptions,
+    limit: usize,
+) {
     ctx.reset();
+    output.clear();
     let mut accumulator = TagAccumulator::new();
 
-    // Adaptive State
-    let mut blob_out_len = 0; // "Uncompressed" bytes represented so far
-    let mut split = 12; // 12 bits Length, 4 bits Offset
-    let mut threshold = 16; // When blob_out_len > threshold, shift parameters
-
     let mut in_idx = 0;
+    // Positions below `inserted` have been added to the hash chains.
+    let mut inserted = 0;
 
-    while in_idx < chunk.len() {
-        // Current max bits allowed for offset based on adaptive split
-        let off_bits = 16 - split;
-        let max_offset = 1 << off_bits;
-
-        let mut best_len = 0;
-        let mut best_off = 0;
+    while in_idx < chunk.len() && output.len <= limit && !output.overflowed {
+        // --- 0. Runs ---
+        // A long run of the previous byte is best encoded as offset-1 tuples, and
+        // searching the hash chains inside it is slow.
+        let run = run_length(chunk, in_idx);
+        if run >= MIN_RUN {
+            let end = in_idx + run;
+            while in_idx < end {
+                let len = (end - in_idx).min((1 << split_at(in_idx)) + 2);
+                if len < MIN_MATCH {
+                    break;
+                }
+                accumulator.push_tuple(1, len, split_at(in_idx), output);
+                in_idx += len;
+            }
+            // Only the end of the run is kept for later matches.
+            inserted = inserted.max(in_idx.saturating_sub(MIN_MATCH));
+            ctx.insert_until(chunk, &mut inserted, in_idx);
+            continue;
+        }
 
         // --- 1. Find Best Match ---
-        if in_idx + MIN_MATCH <= chunk.len() {
-            let hash = hash_3_bytes(&chunk[in_idx..in_idx + 3]);
-            let mut candidate_idx = ctx.head[hash];
-            let mut depth = 0;
-
-            while candidate_idx != EMPTY_ENTRY && depth < MAX_SEARCH_DEPTH {
-                let candidate = candidate_idx as usize;
+        let (mut best_len, mut best_off) = longest_match(chunk, in_idx, ctx, options);
+        let mut literals = 0;
 
-                if candidate >= in_idx {
-                    break; // Should not happen with correct logic
+        // --- 2. Lazy Evaluation ---
+        // Emit literals first if a match starting shortly after is longer by at
+        // least the number of extra literals it needs.
+        if best_len >= MIN_MATCH && best_len < options.nice_length {
+            for step in 1..=options.lazy_steps {
+                ctx.insert_until(chunk, &mut inserted, in_idx + step);
+                let (len, off) = longest_match(chunk, in_idx + step, ctx, options);
+                if len >= MIN_MATCH && len + literals >= best_len + step {
+                    (best_len, best_off, literals) = (len, off, step);
                 }
+            }
+        }
 
-                let dist = in_idx - candidate;
-                if dist >= max_offset {
-                    break; // Too far for current adaptive window
-                }
+        // --- 3. Encode Literals and Match ---
+        for _ in 0..literals {
+            accumulator.push_literal(chunk[in_idx], output);
+            in_idx += 1;
+            ctx.insert_until(chunk, &mut inserted, in_idx);
+        }
 
-                // Optimization: Check the byte at `best_len` to fail fast
-                if in_idx + best_len < chunk.len()
-                    && chunk[candidate + best_len] == chunk[in_idx + best_len]
-                {
-                    let match_len =
-                        common_prefix_len(&chunk[in_idx..], &chunk[candidate..], MAX_MATCH);
-
-                    if match_len >= MIN_MATCH && match_len > best_len {
-                        best_len = match_len;
-                        best_off = dist;
-                        if best_len >= MAX_MATCH {
-                            best_len = MAX_MATCH;
-                            break;
-                        }
-                    }
-                }
+        if best_len >= MIN_MATCH {
+            accumulator.push_tuple(best_off, best_len, split_at(in_idx), output);
 
-                candidate_idx = ctx.next[candidate];
-                depth += 1;
+            if options.insert_matched {
+                // Update hash for all bytes covered by the match
+                ctx.insert_until(chunk, &mut inserted, in_idx + best_len);
+            } else {
+                ctx.insert_until(chunk, &mut inserted, in_idx + 1);
+                inserted = inserted.max(in_idx + best_len);
             }
+            in_idx += best_len;
+        } else {
+            // Literal
+            accumulator.push_literal(chunk[in_idx], output);
+            in_idx += 1;
+            ctx.insert_until(chunk, &mut inserted, in_idx);
         }
+    }
 
-        // --- 2. Encode Match or Literal ---
-        if best_len >= MIN_MATCH {
-            // Clamp length to fit in current `split` bits
-            // Max encodable length = (2^split) + 3 - 1
+    // Flush any remaining items in the accumulator
+    accumulator.flush(output);
+}
+
+/// Scratch memory for the optimal parse of a chunk.
+struct OptimalParser {
+    /// Longest usable match at each position (0 if none).
+    match_len: Vec<u16>,
+    /// Distance of the match at each position.
+    match_off: Vec<u16>,
+    /// Position following the item chosen at each position.
+    next: Vec<u16>,
+    /// Min segment tree over the suffix costs, keyed by `(cost << 13) | !position`
+    /// so that ties resolve to the furthest position.
+    tree: Vec<u32>,
+}
+
+impl OptimalParser {
+    /// Number of bytes allocated for the parser tables.
+    const HEAP_SIZE: usize = 3 * CHUNK_SIZE * size_of::<u16>() + 2 * TREE_LEAVES * size_of::<u32>();
+
+    fn new() -> Self {
+        Self {
+            match_len: alloc::vec![0; CHUNK_SIZE],
+            match_off: alloc::vec![0; CHUNK_SIZE],
+            next: alloc::vec![0; CHUNK_SIZE],
+            tree: alloc::vec![u32::MAX; 2 * TREE_LEAVES],
+        }
+    }
+
+    /// Compresses a single chunk (max 4096 bytes) with an optimal parse.
+    ///
+    /// Every LZNT1 tuple costs 2 bytes whatever its offset and length, so only the
+    /// longest match at each position matters: any shorter length can reuse its
+    /// offset. The cheapest encoding of every suffix of the chunk is computed
+    /// backwards, counting a tag bit per item, then emitted forwards.
+    fn compress_chunk(
+        &mut self,
+        chunk: &[u8],
+        output: &mut ChunkBuffer,
+        ctx: &mut Lznt1Context,
+        options: &CompressOptions,
+    ) {
+        ctx.reset();
+        output.clear();
+        let n = chunk.len();
+
+        // --- 1. Longest usable match at every position ---
+        for in_idx in 0..n {
+            let split = split_at(in_idx);
             let max_len_encodable = (1 << split) + 2;
-            if best_len > max_len_encodable {
-                best_len = max_len_encodable;
+            let (len, off) = find_match(
+                chunk,
+                in_idx,
+                ctx,
+                1 << (16 - split),
+                options.max_chain,
+                max_len_encodable,
+            );
+            self.match_len[in_idx] = len.min(max_len_encodable) as u16;
+            self.match_off[in_idx] = off as u16;
+            ctx.update(chunk, in_idx);
+        }
+
+        // --- 2. Cheapest encoding of every suffix, from the end ---
+        self.tree.fill(u32::MAX);
+        self.set_cost(n, 0);
+        for in_idx in (0..n).rev() {
+            let mut cost = self.cost(in_idx + 1) + LITERAL_COST;
+            let mut next = in_idx + 1;
+
+            let len = self.match_len[in_idx] as usize;
+            if len >= MIN_MATCH {
+                let (tuple_cost, tuple_next) = self.cheapest(in_idx + MIN_MATCH, in_idx + len);
+                if tuple_cost + TUPLE_COST <= cost {
+                    cost = tuple_cost + TUPLE_COST;
+                    next = tuple_next;
+                }
             }
 
-            // Tuple = ((off - 1) << split) | (len - 3)
-            let len_val = best_len - 3;
-            let off_val = best_off - 1;
-            let tuple = ((off_val << split) | len_val) as u16;
+            self.next[in_idx] = next as u16;
+            self.set_cost(in_idx, cost);
+        }
 
-            accumulator.push_tuple(tuple, output);
+        // --- 3. Emit the chosen items ---
+        let mut accumulator = TagAccumulator::new();
+        let mut in_idx = 0;
+        while in_idx < n {
+            let next = self.next[in_idx] as usize;
+            if next - in_idx >= MIN_MATCH {
+                let offset = self.match_off[in_idx] as usize;
+                accumulator.push_tuple(offset, next - in_idx, split_at(in_idx), output);
+            } else {
+                accumulator.push_literal(chunk[in_idx], output);
+            }
+            in_idx = next;
+        }
+        accumulator.flush(output);
+    }
+
+    /// Records the cost of encoding the suffix starting at `pos`.
+    fn set_cost(&mut self, pos: usize, cost: u32) {
+        let mut node = TREE_LEAVES + pos;
+        self.tree[node] = (cost << 13) | (TREE_LEAVES - 1 - pos) as u32;
+        while node > 1 {
+            node /= 2;
+            self.tree[node] = self.tree[2 * node].min(self.tree[2 * node + 1]);
+        }
+    }
 
-            // Update hash for all bytes covered by the match
-            for _ in 0..best_len {
-                ctx.update(chunk, in_idx);
-                in_idx += 1;
+    /// Returns the recorded cost of encoding the suffix starting at `pos`.
+    fn cost(&self, pos: usize) -> u32 {
+        self.tree[TREE_LEAVES + pos] >> 13
+    }
+
+    /// Returns the cheapest recorded suffix starting in `first..=last`, as its cost
+    /// and position.
+    fn cheapest(&self, first: usize, last: usize) -> (u32, usize) {
+        let mut lo = TREE_LEAVES + first;
+        let mut hi = TREE_LEAVES + last + 1;
+        let mut key = u32::MAX;
+        while lo < hi {
+            if lo & 1 == 1 {
+                key = key.min(self.tree[lo]);
+                lo += 1;
             }
-            blob_out_len += best_len;
-        } else {
-            // Literal
-            accumulator.push_literal(chunk[in_idx], output);
-            ctx.update(chunk, in_idx);
-            in_idx += 1;
-            blob_out_len += 1;
+            if hi & 1 == 1 {
+                hi -= 1;
+                key = key.min(self.tree[hi]);
+            }
+            lo /= 2;
+            hi /= 2;
+        }
+        (key >> 13, TREE_LEAVES - 1 - (key & 0x1FFF) as usize)
+    }
+}
+
+/// Returns the length bit width of tuples encoded once `pos` bytes of the chunk
+/// have been produced.
+const fn split_at(pos: usize) -> usize {
+    let mut split = 12; // 12 bits Length, 4 bits Offset
+    let mut threshold = 16; // When pos > threshold, shift parameters
+    while pos > threshold {
+        split -= 1;
+        threshold <<= 1;
+    }
+    split
+}
+
+/// Finds the longest match at `in_idx` that can be encoded there, returning its
+/// length (clamped to the current split) and distance.
+fn longest_match(
+    chunk: &[u8],
+    in_idx: usize,
+    ctx: &Lznt1Context,
+    options: &CompressOptions,
+) -> (usize, usize) {
+    // Adaptive split: the "uncompressed" bytes represented so far are `in_idx`.
+    let split = split_at(in_idx);
+
+    // Current max bits allowed for offset based on adaptive split
+    let off_bits = 16 - split;
+    let max_offset = 1 << off_bits;
+
+    let (len, off) = find_match(
+        chunk,
+        in_idx,
+        ctx,
+        max_offset,
+        options.max_chain,
+        options.nice_length,
+    );
+
+    // Clamp length to fit in current `split` bits
+    // Max encodable length = (2^split) + 3 - 1
+    let max_len_encodable = (1 << split) + 2;
+    (len.min(max_len_encodable), off)
+}
+
+/// Searches the hash chains for the longest match at `in_idx` reaching back less
+/// than `max_offset` bytes, returning its length and distance.
+///
+/// At most `max_chain` candidates are inspected, and the search stops early once a
+/// match of `nice_length` bytes is found. A length of 0 means no match was found.
+fn find_match(
+    chunk: &[u8],
+    in_idx: usize,
+    ctx: &Lznt1Context,
+    max_offset: usize,
+    max_chain: usize,
+    nice_length: usize,
+) -> (usize, usize) {
+    let mut best_len = 0;
+    let mut best_off = 0;
+
+    if in_idx + MIN_MATCH > chunk.len() {
+        return (best_len, best_off);
+    }
+
+    let hash = hash_3_bytes(&chunk[in_idx..in_idx + 3]);
+    let mut candidate_idx = ctx.head[hash];
+    let mut depth = 0;
+
+    while candidate_idx != EMPTY_ENTRY && depth < max_chain {
+        let candidate = candidate_idx as usize;
+
+        if candidate >= in_idx {
+            break; // Should not happen with correct logic
+        }
+
+        let dist = in_idx - candidate;
+        if dist >= max_offset {
+            break; // Too far for current adaptive window
         }
 
-        // --- 3. Adaptive Threshold Update ---
-        while blob_out_len > threshold {
-            if split > 0 {
-                split -= 1;
+        // Optimization: Check the byte at `best_len` to fail fast
+        if in_idx + best_len < chunk.len()
+            && chunk[candidate + best_len] == chunk[in_idx + best_len]
+        {
+            let match_len = common_prefix_len(&chunk[in_idx..], &chunk[candidate..], MAX_MATCH);
+
+            if match_len >= MIN_MATCH && match_len > best_len {
+                best_len = match_len;
+                best_off = dist;
+                if best_len >= MAX_MATCH {
+                    best_len = MAX_MATCH;
+                    break;
+                }
+                if best_len >= nice_length {
+                    break; // Good enough for this level
+                }
             }
-            threshold <<= 1;
         }
+
+        candidate_idx = ctx.next[candidate];
+        depth += 1;
     }
 
-    // Flush any remaining items in the accumulator
-    accumulator.flush(output);
+    (best_len, best_off)
+}
+
+/// Formats a match tuple: `((offset - 1) << split) | (length - 3)`.
+#[inline]
+const fn encode_tuple(offset: usize, length: usize, split: usize) -> u16 {
+    (((offset - 1) << split) | (length - 3)) as u16
 }
 
 /// Helper to format the 2-byte chunk header.
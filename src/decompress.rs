@@ -1,85 +1,531 @@
-use crate::error::DecompressionError;
+use crate::chunk::chunks;
+use crate::error::{DecompressionError, ErrorPosition, PartialDecompressionError, SinkFull};
+use crate::sink::{OutputSink, SliceSink};
 use alloc::vec::Vec;
 
 type Result<T> = core::result::Result<T, DecompressionError>;
 
 // --- Constants ---
 
-/// Bitmask to extract the chunk size (lower 12 bits) from the header.
-const HEADER_SIZE_MASK: u16 = 0x0FFF;
-
-/// Bit flag indicating if the chunk is compressed (0xBxxx) or raw (0x3xxx).
-/// LZNT1 typically uses the MSB or specific high nibbles, but checking 0x8000 is sufficient.
-const HEADER_COMPRESSED_FLAG: u16 = 0x8000;
+/// Maximum uncompressed size of a single chunk (4KB).
+pub(crate) const CHUNK_SIZE: usize = 4096;
 
 /// Number of items (literals or tuples) in a single tag group.
-const TAG_GROUP_SIZE: usize = 8;
+pub(crate) const TAG_GROUP_SIZE: usize = 8;
 
 /// Initial bit width for the length component of a match tuple.
-const INITIAL_SPLIT: usize = 12;
+pub(crate) const INITIAL_SPLIT: usize = 12;
 
 /// Initial threshold for the uncompressed size before adaptive state update.
-const INITIAL_THRESHOLD: usize = 16;
+pub(crate) const INITIAL_THRESHOLD: usize = 16;
+
+/// Zeros appended to short non-final chunks when padding is enabled.
+const ZERO_CHUNK: [u8; CHUNK_SIZE] = [0; CHUNK_SIZE];
+
+/// How thoroughly chunk headers are validated during decompression.
+///
+/// Each level includes the checks of the previous ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum ValidationLevel {
+    /// Only the compressed flag (bit 15) and the 12-bit size are interpreted.
+    #[default]
+    Lenient,
+    /// The signature bits 12-14 must read `0b011`, otherwise decompression fails
+    /// with [`DecompressionError::InvalidSignature`].
+    Signature,
+    /// Additionally enforces the framing produced by LZNT1 encoders: raw chunks
+    /// followed by another chunk must hold exactly 4096 bytes
+    /// ([`DecompressionError::ShortRawChunk`]), and a compressed chunk may not be
+    /// larger than its decompressed data stored raw
+    /// ([`DecompressionError::CompressedChunkTooLarge`]).
+    Strict,
+}
+
+/// Options controlling how an LZNT1 stream is decompressed.
+///
+/// The defaults match [`decompress`]: no output limit is applied, strict
+/// chunk-local validation is enabled, headers are validated leniently and short
+/// chunks are not padded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecompressOptions {
+    max_output: usize,
+    strict: bool,
+    validation: ValidationLevel,
+    zero_pad: bool,
+}
+
+impl Default for DecompressOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DecompressOptions {
+    /// Creates the default options.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_output: usize::MAX,
+            strict: true,
+            validation: ValidationLevel::Lenient,
+            zero_pad: false,
+        }
+    }
+
+    /// Limits the number of bytes a single call may append to the output.
+    ///
+    /// Decompression stops with [`DecompressionError::OutputLimitExceeded`] before
+    /// any byte beyond the limit is written or allocated. Use this when the input
+    /// is untrusted, since a tiny stream can expand to a very large output.
+    #[must_use]
+    pub const fn max_output(mut self, limit: usize) -> Self {
+        self.max_output = limit;
+        self
+    }
+
+    /// Enables or disables strict chunk-local validation (enabled by default).
+    ///
+    /// LZNT1 back-references are confined to the chunk being decoded, and a chunk
+    /// never decompresses to more than 4096 bytes. In strict mode, offsets reaching
+    /// before the start of the current chunk fail with
+    /// [`DecompressionError::InvalidOffset`] and oversized chunks fail with
+    /// [`DecompressionError::ChunkOverflow`]. Disabling it resolves offsets against
+    /// the whole output vector, including any bytes it held before the call.
+    #[must_use]
+    pub const fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets how thoroughly chunk headers are validated.
+    ///
+    /// Use a stricter level to decide whether an unknown blob is LZNT1 at all.
+    #[must_use]
+    pub const fn validation(mut self, level: ValidationLevel) -> Self {
+        self.validation = level;
+        self
+    }
+
+    /// Pads short non-final chunks with zeros to 4096 bytes (disabled by default).
+    ///
+    /// Windows treats the missing tail of a chunk that decompresses to fewer than
+    /// 4096 bytes, but is followed by another chunk, as zeros, so that every chunk
+    /// starts on a 4KB boundary of the output. Enable this to reproduce the output
+    /// of `RtlDecompressBuffer` on such streams, as found on NTFS volumes.
+    #[must_use]
+    pub const fn zero_pad(mut self, zero_pad: bool) -> Self {
+        self.zero_pad = zero_pad;
+        self
+    }
+}
+
+/// Progress of a successful [`decompress_with_status`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DecompressStatus {
+    /// Number of input bytes making up the stream, including its end-of-stream
+    /// marker or trailing null byte.
+    pub consumed: usize,
+    /// Number of bytes appended to the output.
+    pub produced: usize,
+    /// Whether the stream ended at an end-of-stream marker (or trailing null
+    /// byte) rather than at the end of the input.
+    pub terminated: bool,
+}
 
 /// Decompresses an entire LZNT1 stream.
 ///
 /// The input is processed in chunks (headers + data). The function manages
 /// output capacity reservation and validates the integrity of chunk headers.
 pub fn decompress(input: &[u8], output: &mut Vec<u8>) -> Result<()> {
-    // Heuristic capacity reservation to reduce allocation churn.
-    let heuristic_cap = input.len();
-    if output.capacity() < output.len() + heuristic_cap {
-        output.reserve(heuristic_cap);
-    }
+    decompress_with(input, output, &DecompressOptions::new())
+}
 
-    let mut in_pos = 0;
-    let end = input.len();
+/// Decompresses an entire LZNT1 stream using the given [`DecompressOptions`].
+///
+/// Behaves like [`decompress`], but enforces the configured output limit.
+/// On error, `output` may contain the data decoded before the failure.
+pub fn decompress_with(
+    input: &[u8],
+    output: &mut Vec<u8>,
+    options: &DecompressOptions,
+) -> Result<()> {
+    decompress_vec(input, output, options).map(|_| ())
+}
 
-    while in_pos < end {
-        // LZNT1 streams may be null-terminated (single 0x00 byte at EOF).
-        if in_pos + 1 == end && input[in_pos] == 0 {
+/// Decompresses an LZNT1 stream embedded in a larger buffer, reporting where it ended.
+///
+/// Behaves like [`decompress_with`], but returns a [`DecompressStatus`] giving the
+/// number of input bytes making up the stream (including its end-of-stream marker)
+/// and the number of bytes appended to `output`. Decoding stops at the `0x0000`
+/// marker, so any data following the stream is left untouched.
+///
+/// On error, the returned [`PartialDecompressionError`] also reports how many
+/// bytes were appended to `output` before the failure; they are left in place.
+///
+/// # Example
+///
+/// ```rust
+/// use lznt1::{DecompressOptions, compress, decompress_with_status};
+///
+/// let mut record = Vec::new();
+/// compress(b"payload", &mut record);
+/// record.extend_from_slice(&[0x00, 0x00]); // End-of-stream marker
+/// record.extend_from_slice(b"next field");
+///
+/// let mut output = Vec::new();
+/// let status = decompress_with_status(&record, &mut output, &DecompressOptions::new()).unwrap();
+/// assert_eq!(output, b"payload");
+/// assert!(status.terminated);
+/// assert_eq!(&record[status.consumed..], b"next field");
+/// ```
+pub fn decompress_with_status(
+    input: &[u8],
+    output: &mut Vec<u8>,
+    options: &DecompressOptions,
+) -> core::result::Result<DecompressStatus, PartialDecompressionError> {
+    let start = output.len();
+    decompress_vec(input, output, options).map_err(|error| PartialDecompressionError {
+        error,
+        produced: output.len() - start,
+    })
+}
+
+/// Decompresses an entire LZNT1 stream into a caller-provided buffer.
+///
+/// This is the allocation-free counterpart of [`decompress`], mirroring
+/// `RtlDecompressBuffer`: the output is written to the start of `output` and the
+/// number of bytes produced is returned. If the decompressed data does not fit,
+/// [`DecompressionError::BufferTooSmall`] is returned.
+pub fn decompress_into(input: &[u8], output: &mut [u8]) -> Result<usize> {
+    let mut sink = SliceSink::new(output);
+    decompress_to_sink(input, &mut sink, &DecompressOptions::new())?;
+    Ok(sink.len())
+}
+
+/// Decompresses an entire LZNT1 stream into any [`OutputSink`].
+///
+/// Behaves like [`decompress_with`], with the configured output limit applied to
+/// the bytes appended to `output`. A sink that runs out of space fails with
+/// [`DecompressionError::BufferTooSmall`].
+///
+/// # Example
+///
+/// ```rust
+/// use lznt1::{CountingSink, DecompressOptions, compress, decompress_to_sink};
+///
+/// let mut compressed = Vec::new();
+/// compress(&[7u8; 100_000], &mut compressed);
+///
+/// // Measure the decompressed size without materializing it.
+/// let mut counter = CountingSink::new();
+/// decompress_to_sink(&compressed, &mut counter, &DecompressOptions::new()).unwrap();
+/// assert_eq!(counter.count(), 100_000);
+/// ```
+pub fn decompress_to_sink<S: OutputSink + ?Sized>(
+    input: &[u8],
+    output: &mut S,
+    options: &DecompressOptions,
+) -> Result<()> {
+    decompress_stream(input, &mut Output::new(output, options.max_output), options).map(|_| ())
+}
+
+/// Decompresses part of a stream, starting at `uncompressed_offset` in the
+/// decompressed data, into `output`.
+///
+/// This is the equivalent of `RtlDecompressFragment`. Every chunk except the last
+/// represents exactly 4096 decompressed bytes, so chunks ending before the
+/// requested range are skipped using their headers alone, without being decoded.
+/// Only the chunks overlapping the range are decompressed, which allows seeking
+/// into large streams in time proportional to the number of chunk headers.
+///
+/// A non-final chunk decompressing to fewer than 4096 bytes is padded with
+/// zeros, matching Windows. Back-references are resolved within each chunk.
+///
+/// Returns the number of bytes written, which is less than `output.len()` only
+/// if the decompressed data ends before the buffer is full.
+pub fn decompress_fragment(
+    input: &[u8],
+    uncompressed_offset: usize,
+    output: &mut [u8],
+) -> Result<usize> {
+    let mut window = [0u8; CHUNK_SIZE];
+    let mut chunks = chunks(input);
+    let mut written = 0;
+
+    while written < output.len() {
+        let Some(chunk) = chunks.next() else {
             break;
+        };
+        let chunk = chunk.map_err(|e| {
+            let chunk_start = e.position().chunk_index * CHUNK_SIZE;
+            at_output_offset(e, chunk_start)
+        })?;
+        let chunk_start = chunk.index * CHUNK_SIZE;
+
+        // Skip chunks that end before the requested range without decoding them.
+        if chunk_start + CHUNK_SIZE <= uncompressed_offset {
+            continue;
         }
 
-        // Ensure we can read the 2-byte header.
-        if in_pos + 2 > end {
-            return Err(DecompressionError::UnexpectedEof);
+        let location = chunk.location(chunk_start);
+        let mut produced =
+            decompress_chunk(chunk.body, chunk.header.compressed, &mut window, location)?;
+
+        if produced < CHUNK_SIZE && chunks.has_next_chunk() {
+            window[produced..].fill(0);
+            produced = CHUNK_SIZE;
         }
 
-        let header = u16::from_le_bytes([input[in_pos], input[in_pos + 1]]);
-        in_pos += 2;
+        // Position of the next requested byte within this chunk.
+        let from = uncompressed_offset + written - chunk_start;
+        if from >= produced {
+            break; // The requested range starts past the end of the data.
+        }
+
+        let n = (produced - from).min(output.len() - written);
+        output[written..written + n].copy_from_slice(&window[from..from + n]);
+        written += n;
+    }
+
+    Ok(written)
+}
+
+/// Sets the chunk output offset of an error raised while walking chunk headers.
+pub(crate) fn at_output_offset(
+    err: DecompressionError,
+    chunk_output_offset: usize,
+) -> DecompressionError {
+    let position = ErrorPosition {
+        chunk_output_offset,
+        ..*err.position()
+    };
+    err.with_position(position)
+}
+
+/// Destination for decompressed bytes.
+///
+/// Wraps an [`OutputSink`] so that the chunk and tag-group logic is shared by every
+/// entry point, and enforces the output limit before every write. Errors are
+/// returned with a placeholder [`ErrorPosition`], which the decoder replaces with
+/// the actual location.
+struct Output<'a, S: ?Sized> {
+    sink: &'a mut S,
+    /// Length the sink may grow to.
+    limit: usize,
+}
 
-        if header == 0 {
-            break; // Standard End-of-Stream marker
+impl<'a, S: OutputSink + ?Sized> Output<'a, S> {
+    /// Wraps `sink`, allowing at most `max_output` more bytes to be written to it.
+    fn new(sink: &'a mut S, max_output: usize) -> Self {
+        let limit = sink.len().saturating_add(max_output);
+        Self { sink, limit }
+    }
+
+    /// Returns the number of bytes written so far (the available history).
+    #[inline]
+    fn len(&self) -> usize {
+        self.sink.len()
+    }
+
+    /// Ensures that appending `additional` bytes keeps the output within `limit`.
+    ///
+    /// Called before every write so that the limit is enforced without allocating first.
+    #[inline]
+    fn check_limit(&self, additional: usize) -> Result<()> {
+        if additional > self.limit - self.sink.len() {
+            return Err(DecompressionError::OutputLimitExceeded {
+                position: ErrorPosition::UNKNOWN,
+            });
         }
+        Ok(())
+    }
+
+    /// Appends a single byte.
+    #[inline]
+    fn push(&mut self, byte: u8) -> Result<()> {
+        self.check_limit(1)?;
+        self.sink.push(byte).map_err(buffer_too_small)
+    }
+
+    /// Appends a slice of bytes.
+    #[inline]
+    fn extend_from_slice(&mut self, bytes: &[u8]) -> Result<()> {
+        self.check_limit(bytes.len())?;
+        self.sink.extend_from_slice(bytes).map_err(buffer_too_small)
+    }
+
+    /// Appends `length` bytes copied from `offset` bytes back in the history.
+    ///
+    /// The caller guarantees `1 <= offset <= self.len()`.
+    #[inline]
+    fn copy_match(&mut self, offset: usize, length: usize) -> Result<()> {
+        self.check_limit(length)?;
+        self.sink
+            .copy_within_history(offset, length)
+            .map_err(buffer_too_small)
+    }
+}
 
-        let size = ((header & HEADER_SIZE_MASK) + 1) as usize;
-        let is_compressed = (header & HEADER_COMPRESSED_FLAG) != 0;
+/// Converts a full sink into [`DecompressionError::BufferTooSmall`].
+#[inline]
+const fn buffer_too_small(_: SinkFull) -> DecompressionError {
+    DecompressionError::BufferTooSmall {
+        position: ErrorPosition::UNKNOWN,
+    }
+}
 
-        // Ensure the chunk body is within bounds.
-        if in_pos + size > end {
-            return Err(DecompressionError::InputTooShort);
+/// Decompresses a stream into a vector, enforcing the configured output limit.
+fn decompress_vec(
+    input: &[u8],
+    output: &mut Vec<u8>,
+    options: &DecompressOptions,
+) -> Result<DecompressStatus> {
+    // Heuristic capacity reservation to reduce allocation churn.
+    let heuristic_cap = input.len().min(options.max_output);
+    if output.capacity() < output.len() + heuristic_cap {
+        output.reserve(heuristic_cap);
+    }
+
+    decompress_stream(input, &mut Output::new(output, options.max_output), options)
+}
+
+/// Walks the chunk headers of a stream and decodes every chunk into `output`.
+fn decompress_stream<S: OutputSink + ?Sized>(
+    input: &[u8],
+    output: &mut Output<'_, S>,
+    options: &DecompressOptions,
+) -> Result<DecompressStatus> {
+    let start_out_len = output.len();
+
+    // Set when the previous chunk was stored raw with fewer than 4096 bytes.
+    let mut short_raw_chunk = false;
+
+    let mut chunks = chunks(input);
+    while let Some(chunk) = chunks.next() {
+        let produced = output.len() - start_out_len;
+        let chunk = chunk.map_err(|e| at_output_offset(e, produced))?;
+        let header_pos = ErrorPosition {
+            input_offset: chunk.offset,
+            chunk_index: chunk.index,
+            chunk_output_offset: produced,
+        };
+
+        if options.validation >= ValidationLevel::Signature && !chunk.header.has_valid_signature() {
+            return Err(DecompressionError::InvalidSignature {
+                position: header_pos,
+            });
+        }
+
+        // Only the final chunk of a stream may be a short raw chunk.
+        if options.validation >= ValidationLevel::Strict && short_raw_chunk {
+            return Err(DecompressionError::ShortRawChunk {
+                position: header_pos,
+            });
         }
 
-        let block_slice = &input[in_pos..in_pos + size];
+        let size = chunk.header.body_len;
+        let location = chunk.location(produced);
+
+        if chunk.header.compressed {
+            let start_len = output.len();
+            decompress_compressed_block(chunk.body, output, options.strict, location)?;
 
-        if is_compressed {
-            decompress_compressed_block(block_slice, output)?;
+            if options.validation >= ValidationLevel::Strict && size > output.len() - start_len {
+                return Err(DecompressionError::CompressedChunkTooLarge {
+                    position: header_pos,
+                });
+            }
         } else {
             // Raw block: direct copy
-            output.extend_from_slice(block_slice);
+            output
+                .extend_from_slice(chunk.body)
+                .map_err(|e| e.with_position(location.at(0)))?;
+            short_raw_chunk = size < CHUNK_SIZE;
         }
 
-        in_pos += size;
+        let chunk_len = output.len() - start_out_len - produced;
+        if options.zero_pad && chunk_len < CHUNK_SIZE && chunks.has_next_chunk() {
+            output
+                .extend_from_slice(&ZERO_CHUNK[chunk_len..])
+                .map_err(|e| e.with_position(location.at(size)))?;
+        }
     }
 
-    Ok(())
+    Ok(DecompressStatus {
+        consumed: chunks.offset(),
+        produced: output.len() - start_out_len,
+        terminated: chunks.is_terminated(),
+    })
+}
+
+/// Location of a chunk within the stream, used to attach positions to errors.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ChunkLocation {
+    /// Input offset of the chunk body (just past its header).
+    pub(crate) body_offset: usize,
+    /// Zero-based index of the chunk.
+    pub(crate) index: usize,
+    /// Number of bytes produced before the chunk started.
+    pub(crate) output_offset: usize,
+}
+
+impl ChunkLocation {
+    /// Returns the error position of the byte at `body_idx` within the chunk body.
+    #[inline]
+    pub(crate) const fn at(self, body_idx: usize) -> ErrorPosition {
+        ErrorPosition {
+            input_offset: self.body_offset + body_idx,
+            chunk_index: self.index,
+            chunk_output_offset: self.output_offset,
+        }
+    }
+}
+
+/// Decodes a single chunk body into `output`, returning the number of bytes produced.
+///
+/// Back-references are resolved within the chunk, and a chunk that expands beyond
+/// [`CHUNK_SIZE`] bytes is rejected with [`DecompressionError::ChunkOverflow`].
+pub(crate) fn decompress_chunk(
+    body: &[u8],
+    is_compressed: bool,
+    output: &mut [u8],
+    location: ChunkLocation,
+) -> Result<usize> {
+    let (produced, result) = decompress_chunk_partial(body, is_compressed, output, location);
+    result.map(|()| produced)
+}
+
+/// Like [`decompress_chunk`], but also returns the number of bytes written to
+/// `output` when decoding fails part way through the chunk.
+pub(crate) fn decompress_chunk_partial(
+    body: &[u8],
+    is_compressed: bool,
+    output: &mut [u8],
+    location: ChunkLocation,
+) -> (usize, Result<()>) {
+    let mut sink = SliceSink::new(output);
+    let mut out = Output::new(&mut sink, usize::MAX);
+    let result = if is_compressed {
+        decompress_compressed_block(body, &mut out, true, location)
+    } else {
+        out.extend_from_slice(body)
+            .map_err(|e| e.with_position(location.at(0)))
+    };
+    (sink.len(), result)
 }
 
 /// Decompresses a single compressed LZNT1 block.
 ///
 /// Handles the "Tag Group" logic, adaptive window splitting, and LZ matches.
-fn decompress_compressed_block(input: &[u8], output: &mut Vec<u8>) -> Result<()> {
+/// In `strict` mode, matches may only reference bytes of this block and the
+/// block may not expand beyond [`CHUNK_SIZE`] bytes. Errors are reported
+/// relative to `location`.
+fn decompress_compressed_block<S: OutputSink + ?Sized>(
+    input: &[u8],
+    output: &mut Output<'_, S>,
+    strict: bool,
+    location: ChunkLocation,
+) -> Result<()> {
     let mut in_idx = 0;
     let end = input.len();
 
@@ -89,6 +535,13 @@ fn decompress_compressed_block(input: &[u8], output: &mut Vec<u8>) -> Result<()>
     let mut threshold = INITIAL_THRESHOLD;
     let start_out_len = output.len();
 
+    // Bytes this block may still produce, and the start of its usable history.
+    let (chunk_cap, history_start) = if strict {
+        (CHUNK_SIZE, start_out_len)
+    } else {
+        (usize::MAX, 0)
+    };
+
     while in_idx < end {
         // 1. Load Tag Byte
         let tag_byte = input[in_idx];
@@ -98,7 +551,9 @@ fn decompress_compressed_block(input: &[u8], output: &mut Vec<u8>) -> Result<()>
         // If tag is 0, the next 8 items are literals.
         // We only take this path if we have enough bytes remaining to avoid EOF checks.
         if tag_byte == 0 && in_idx + TAG_GROUP_SIZE <= end {
-            output.extend_from_slice(&input[in_idx..in_idx + TAG_GROUP_SIZE]);
+            check_chunk_cap(output.len() - start_out_len, TAG_GROUP_SIZE, chunk_cap)
+                .and_then(|()| output.extend_from_slice(&input[in_idx..in_idx + TAG_GROUP_SIZE]))
+                .map_err(|e| e.with_position(location.at(in_idx)))?;
             in_idx += TAG_GROUP_SIZE;
 
             // Update adaptive parameters for the 8 bytes just added.
@@ -118,9 +573,12 @@ fn decompress_compressed_block(input: &[u8], output: &mut Vec<u8>) -> Result<()>
             if is_link {
                 // Ensure we have 2 bytes for the tuple.
                 if in_idx + 2 > end {
-                    return Err(DecompressionError::UnexpectedEof);
+                    return Err(DecompressionError::UnexpectedEof {
+                        position: location.at(in_idx),
+                    });
                 }
 
+                let tuple_idx = in_idx;
                 let tuple = u16::from_le_bytes([input[in_idx], input[in_idx + 1]]) as usize;
                 in_idx += 2;
 
@@ -128,7 +586,17 @@ fn decompress_compressed_block(input: &[u8], output: &mut Vec<u8>) -> Result<()>
                 let length = (tuple & mask) + 3;
                 let offset = (tuple >> split) + 1;
 
-                apply_match(output, length, offset)?;
+                let available = output.len() - history_start;
+                if offset > available {
+                    return Err(DecompressionError::InvalidOffset {
+                        position: location.at(tuple_idx),
+                        offset,
+                        available,
+                    });
+                }
+                check_chunk_cap(output.len() - start_out_len, length, chunk_cap)
+                    .and_then(|()| output.copy_match(offset, length))
+                    .map_err(|e| e.with_position(location.at(tuple_idx)))?;
             } else {
                 // Literal
                 if in_idx >= end {
@@ -136,7 +604,9 @@ fn decompress_compressed_block(input: &[u8], output: &mut Vec<u8>) -> Result<()>
                     // This is a permissive behavior required by LZNT1 specs.
                     return Ok(());
                 }
-                output.push(input[in_idx]);
+                check_chunk_cap(output.len() - start_out_len, 1, chunk_cap)
+                    .and_then(|()| output.push(input[in_idx]))
+                    .map_err(|e| e.with_position(location.at(in_idx)))?;
                 in_idx += 1;
             }
 
@@ -158,40 +628,21 @@ fn decompress_compressed_block(input: &[u8], output: &mut Vec<u8>) -> Result<()>
     Ok(())
 }
 
-/// Applies an LZ77 match to the output buffer.
-///
-/// Handles data copying from the existing output history. Includes an optimization
-/// for Run-Length Encoding (RLE) where offset is 1.
+/// Ensures a block that produced `block_len` bytes can grow by `additional` bytes.
 #[inline]
-fn apply_match(output: &mut Vec<u8>, length: usize, offset: usize) -> Result<()> {
-    if offset > output.len() {
-        return Err(DecompressionError::InvalidOffset);
+const fn check_chunk_cap(block_len: usize, additional: usize, cap: usize) -> Result<()> {
+    if additional > cap - block_len {
+        return Err(DecompressionError::ChunkOverflow {
+            position: ErrorPosition::UNKNOWN,
+        });
     }
-
-    output.reserve(length);
-
-    // --- RLE Fast Path (Offset == 1) ---
-    // Since offset > 0 (checked implicitly by offset > output.len() if output is empty),
-    // and we know output.len() >= offset, output is not empty here.
-    if offset == 1 {
-        let last_byte = output[output.len() - 1];
-        output.resize(output.len() + length, last_byte);
-    } else {
-        // Standard LZ77 Copy (supports overlapping ranges)
-        let src_pos = output.len() - offset;
-        for k in 0..length {
-            let val = output[src_pos + k];
-            output.push(val);
-        }
-    }
-
     Ok(())
 }
 
 /// Updates the adaptive window parameters (split, mask, threshold) based on
 /// the current uncompressed block size.
 #[inline]
-const fn update_adaptive_state(
+pub(crate) const fn update_adaptive_state(
     current_block_out_len: usize,
     threshold: &mut usize,
     split: &mut usize,
@@ -1,16 +1,132 @@
+use core::fmt;
+
 use thiserror::Error;
 
+/// Location in a compressed stream at which decompression failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ErrorPosition {
+    /// Offset of the offending header, tag byte, literal or tuple in the input.
+    pub input_offset: usize,
+    /// Zero-based index of the chunk being decoded.
+    pub chunk_index: usize,
+    /// Number of bytes produced before the chunk started.
+    pub chunk_output_offset: usize,
+}
+
+impl ErrorPosition {
+    /// Placeholder used until the decoder attaches the actual location.
+    pub(crate) const UNKNOWN: Self = Self {
+        input_offset: 0,
+        chunk_index: 0,
+        chunk_output_offset: 0,
+    };
+}
+
+impl fmt::Display for ErrorPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "input offset {} (chunk {}, chunk output offset {})",
+            self.input_offset, self.chunk_index, self.chunk_output_offset
+        )
+    }
+}
+
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum DecompressionError {
-    #[error("Unexpected end of stream")]
-    UnexpectedEof,
+    #[error("Unexpected end of stream at {position}")]
+    UnexpectedEof { position: ErrorPosition },
+
+    #[error("Lookback offset {offset} out of bounds ({available} bytes of history) at {position}")]
+    InvalidOffset {
+        position: ErrorPosition,
+        /// Distance the tuple reaches back.
+        offset: usize,
+        /// Number of bytes of history available to the tuple.
+        available: usize,
+    },
+
+    #[error("Input buffer too short for expected data at {position}")]
+    InputTooShort { position: ErrorPosition },
+
+    #[error("Decompressed output exceeds the configured limit at {position}")]
+    OutputLimitExceeded { position: ErrorPosition },
+
+    #[error("Output buffer too small for decompressed data at {position}")]
+    BufferTooSmall { position: ErrorPosition },
 
-    #[error("Invalid block header")]
-    InvalidHeader,
+    #[error("Chunk decompresses to more than 4096 bytes at {position}")]
+    ChunkOverflow { position: ErrorPosition },
 
-    #[error("Lookback offset out of bounds")]
-    InvalidOffset,
+    #[error("Chunk header signature is not 0b011 at {position}")]
+    InvalidSignature { position: ErrorPosition },
 
-    #[error("Input buffer too short for expected data")]
-    InputTooShort,
+    #[error("Raw chunk shorter than 4096 bytes is followed by another chunk at {position}")]
+    ShortRawChunk { position: ErrorPosition },
+
+    #[error("Compressed chunk is larger than its decompressed data at {position}")]
+    CompressedChunkTooLarge { position: ErrorPosition },
+}
+
+impl DecompressionError {
+    /// Returns the location in the compressed stream at which the error occurred.
+    #[must_use]
+    pub const fn position(&self) -> &ErrorPosition {
+        match self {
+            Self::UnexpectedEof { position }
+            | Self::InvalidOffset { position, .. }
+            | Self::InputTooShort { position }
+            | Self::OutputLimitExceeded { position }
+            | Self::BufferTooSmall { position }
+            | Self::ChunkOverflow { position }
+            | Self::InvalidSignature { position }
+            | Self::ShortRawChunk { position }
+            | Self::CompressedChunkTooLarge { position } => position,
+        }
+    }
+
+    /// Replaces the location of the error.
+    #[must_use]
+    pub(crate) const fn with_position(mut self, new_position: ErrorPosition) -> Self {
+        match &mut self {
+            Self::UnexpectedEof { position }
+            | Self::InvalidOffset { position, .. }
+            | Self::InputTooShort { position }
+            | Self::OutputLimitExceeded { position }
+            | Self::BufferTooSmall { position }
+            | Self::ChunkOverflow { position }
+            | Self::InvalidSignature { position }
+            | Self::ShortRawChunk { position }
+            | Self::CompressedChunkTooLarge { position } => *position = new_position,
+        }
+        self
+    }
 }
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CompressionError {
+    #[error("Output buffer too small for compressed data ({required} bytes required)")]
+    BufferTooSmall {
+        /// Size of the complete compressed output.
+        required: usize,
+    },
+}
+
+/// Error returned by [`decompress_with_status`](crate::decompress_with_status),
+/// reporting the output produced before the failure.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{error} ({produced} bytes decompressed before the failure)")]
+pub struct PartialDecompressionError {
+    /// The underlying decompression error.
+    #[source]
+    pub error: DecompressionError,
+    /// Number of bytes appended to the output before the failure.
+    pub produced: usize,
+}
+
+/// Error returned by an [`OutputSink`](crate::OutputSink) that cannot accept more bytes.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[error("Output sink is full")]
+pub struct SinkFull;
@@ -0,0 +1,386 @@
+//! Streaming adapters over [`std::io`] (requires the `std` feature).
+
+use std::io::{self, Read, Write};
+
+use crate::chunk::ChunkHeader;
+use crate::compress::{ChunkScratch, CompressOptions, END_MARKER, Lznt1Context};
+use crate::decompress::{CHUNK_SIZE, ChunkLocation, decompress_chunk};
+use crate::error::{DecompressionError, ErrorPosition};
+
+/// Maximum size of a chunk body as declared by the 12-bit header field.
+const MAX_BODY_SIZE: usize = 4096;
+
+/// A streaming LZNT1 decompressor implementing [`Read`].
+///
+/// The decoder pulls one chunk header and body at a time from the inner reader
+/// and keeps only the current chunk (at most 4KB compressed and 4KB decompressed)
+/// in memory, so arbitrarily large streams can be processed with constant memory.
+///
+/// Back-references are resolved within the current chunk, as produced by every
+/// LZNT1 encoder. The stream ends at the end of the inner reader or at a `0x0000`
+/// end-of-stream marker, after which nothing more is read from the inner reader.
+///
+/// Malformed input is reported as an [`io::Error`] of kind
+/// [`io::ErrorKind::InvalidData`] wrapping the underlying [`DecompressionError`].
+/// A failed read stops the decoder: every later read fails with the same error.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io::Read;
+/// use lznt1::{Lznt1Decoder, compress};
+///
+/// let mut compressed = Vec::new();
+/// compress(b"Hello world, hello world, hello world", &mut compressed);
+///
+/// let mut decoder = Lznt1Decoder::new(compressed.as_slice());
+/// let mut text = String::new();
+/// decoder.read_to_string(&mut text).unwrap();
+/// assert_eq!(text, "Hello world, hello world, hello world");
+/// ```
+pub struct Lznt1Decoder<R> {
+    inner: R,
+    body: [u8; MAX_BODY_SIZE],
+    window: [u8; CHUNK_SIZE],
+    window_len: usize,
+    window_pos: usize,
+    finished: bool,
+    failure: Option<Failure>,
+
+    // Stream position, used to report error locations.
+    input_offset: usize,
+    chunk_index: usize,
+    output_offset: usize,
+}
+
+impl<R: Read> Lznt1Decoder<R> {
+    /// Creates a decoder reading compressed data from `inner`.
+    pub const fn new(inner: R) -> Self {
+        Self {
+            inner,
+            body: [0; MAX_BODY_SIZE],
+            window: [0; CHUNK_SIZE],
+            window_len: 0,
+            window_pos: 0,
+            finished: false,
+            failure: None,
+            input_offset: 0,
+            chunk_index: 0,
+            output_offset: 0,
+        }
+    }
+
+    /// Returns a shared reference to the inner reader.
+    pub const fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner reader.
+    ///
+    /// Reading from it directly will corrupt the decoder's view of the stream.
+    pub const fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the decoder, returning the inner reader.
+    ///
+    /// Any decompressed bytes not yet read are discarded.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Reads and decodes the next chunk into the window.
+    ///
+    /// Returns `false` once the end of the stream has been reached.
+    fn fill_window(&mut self) -> io::Result<bool> {
+        loop {
+            if self.finished {
+                return Ok(false);
+            }
+
+            let header_pos = ErrorPosition {
+                input_offset: self.input_offset,
+                chunk_index: self.chunk_index,
+                chunk_output_offset: self.output_offset,
+            };
+
+            let mut header = [0u8; ChunkHeader::SIZE];
+            let read = read_fully(&mut self.inner, &mut header)?;
+            self.input_offset += read;
+            match read {
+                // Clean end of the inner reader.
+                0 => {
+                    self.finished = true;
+                    return Ok(false);
+                }
+                // LZNT1 streams may be null-terminated (single 0x00 byte at EOF).
+                1 if header[0] == 0 => {
+                    self.finished = true;
+                    return Ok(false);
+                }
+                1 => {
+                    return Err(invalid_data(DecompressionError::UnexpectedEof {
+                        position: header_pos,
+                    }));
+                }
+                _ => {}
+            }
+
+            let Some(header) = ChunkHeader::parse(u16::from_le_bytes(header)) else {
+                // Standard End-of-Stream marker
+                self.finished = true;
+                return Ok(false);
+            };
+
+            let location = ChunkLocation {
+                body_offset: self.input_offset,
+                index: self.chunk_index,
+                output_offset: self.output_offset,
+            };
+
+            let size = header.body_len;
+            let body = &mut self.body[..size];
+            let read = read_fully(&mut self.inner, body)?;
+            self.input_offset += read;
+            if read < size {
+                return Err(invalid_data(DecompressionError::InputTooShort {
+                    position: header_pos,
+                }));
+            }
+
+            // The body has been consumed even if it fails to decode.
+            let decoded = decompress_chunk(body, header.compressed, &mut self.window, location);
+            self.chunk_index += 1;
+            self.window_len = decoded.map_err(invalid_data)?;
+            self.window_pos = 0;
+            self.output_offset += self.window_len;
+
+            // Chunks that decode to nothing are skipped.
+            if self.window_len > 0 {
+                return Ok(true);
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for Lznt1Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if let Some(failure) = &self.failure {
+            return Err(failure.to_error());
+        }
+        if self.window_pos == self.window_len {
+            match self.fill_window() {
+                Ok(true) => {}
+                Ok(false) => return Ok(0),
+                Err(err) => {
+                    self.failure = Some(Failure::from_error(&err));
+                    return Err(err);
+                }
+            }
+        }
+
+        let available = &self.window[self.window_pos..self.window_len];
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.window_pos += n;
+        Ok(n)
+    }
+}
+
+/// The error that stopped a [`Lznt1Decoder`], kept to fail every later read.
+#[derive(Debug, Clone)]
+enum Failure {
+    /// The input is malformed.
+    Data(DecompressionError),
+    /// The inner reader failed with an error of this kind.
+    Io(io::ErrorKind),
+}
+
+impl Failure {
+    fn from_error(err: &io::Error) -> Self {
+        match err
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<DecompressionError>())
+        {
+            Some(err) => Self::Data(err.clone()),
+            None => Self::Io(err.kind()),
+        }
+    }
+
+    fn to_error(&self) -> io::Error {
+        match self {
+            Self::Data(err) => invalid_data(err.clone()),
+            Self::Io(kind) => io::Error::new(*kind, "the inner reader failed earlier"),
+        }
+    }
+}
+
+/// A streaming LZNT1 compressor implementing [`Write`].
+///
+/// Written bytes are buffered into 4096-byte chunks, and each complete chunk is
+/// compressed with a reused [`Lznt1Context`] and written to the inner writer as a
+/// header and body. The output is the same as [`compress_with`](crate::compress_with)
+/// over everything written, so data can be compressed as it is produced.
+///
+/// [`finish`](Self::finish) must be called once all data has been written: it
+/// writes the final partial chunk and the optional `0x0000` end-of-stream marker.
+/// Dropping the encoder without finishing it loses the buffered bytes.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io::Write;
+/// use lznt1::{Lznt1Encoder, compress, decompress};
+///
+/// let mut encoder = Lznt1Encoder::new(Vec::new());
+/// for line in 0..1000 {
+///     writeln!(encoder, "log line {line}").unwrap();
+/// }
+/// let compressed = encoder.finish().unwrap();
+///
+/// let mut output = Vec::new();
+/// decompress(&compressed, &mut output).unwrap();
+/// assert!(output.starts_with(b"log line 0\nlog line 1\n"));
+/// ```
+pub struct Lznt1Encoder<W: Write> {
+    inner: W,
+    options: CompressOptions,
+    ctx: Lznt1Context,
+    scratch: ChunkScratch,
+    chunk: [u8; CHUNK_SIZE],
+    chunk_len: usize,
+    /// Bytes of the buffered chunk's header and body already written, so that a
+    /// write retried after an inner writer error resumes where it stopped.
+    written: usize,
+}
+
+impl<W: Write> Lznt1Encoder<W> {
+    /// Creates an encoder writing compressed data to `inner` with the default
+    /// [`CompressOptions`].
+    pub fn new(inner: W) -> Self {
+        Self::with_options(inner, CompressOptions::new())
+    }
+
+    /// Creates an encoder writing compressed data to `inner` with the given options.
+    pub fn with_options(inner: W, options: CompressOptions) -> Self {
+        Self {
+            inner,
+            options,
+            ctx: Lznt1Context::new(),
+            scratch: ChunkScratch::new(&options),
+            chunk: [0; CHUNK_SIZE],
+            chunk_len: 0,
+            written: 0,
+        }
+    }
+
+    /// Sets whether [`finish`](Self::finish) appends the `0x0000` end-of-stream
+    /// marker, overriding [`CompressOptions::end_marker`].
+    #[must_use]
+    pub fn end_marker(mut self, enabled: bool) -> Self {
+        self.options = self.options.end_marker(enabled);
+        self
+    }
+
+    /// Returns a shared reference to the inner writer.
+    pub const fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner writer.
+    ///
+    /// Writing to it directly will corrupt the compressed stream.
+    pub const fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Writes the buffered partial chunk and the end-of-stream marker if enabled,
+    /// flushes the inner writer and returns it.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_chunk()?;
+        if self.options.end_marker {
+            self.inner.write_all(&END_MARKER)?;
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Compresses the buffered chunk, if any, and writes it to the inner writer.
+    ///
+    /// If the inner writer fails, the chunk stays buffered. Encoding it again
+    /// yields the same bytes, so the next attempt skips those already written.
+    fn write_chunk(&mut self) -> io::Result<()> {
+        if self.chunk_len > 0 {
+            let chunk = &self.chunk[..self.chunk_len];
+            let (header, body, _) = self.scratch.encode(chunk, &mut self.ctx, &self.options);
+            while self.written < header.len() + body.len() {
+                let rest = match self.written.checked_sub(header.len()) {
+                    Some(in_body) => &body[in_body..],
+                    None => &header[self.written..],
+                };
+                match self.inner.write(rest) {
+                    Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                    Ok(n) => self.written += n,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            self.chunk_len = 0;
+            self.written = 0;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for Lznt1Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // A full chunk is only written once more data arrives, so that a write
+        // never fails after accepting bytes.
+        if self.chunk_len == CHUNK_SIZE {
+            self.write_chunk()?;
+        }
+
+        let n = buf.len().min(CHUNK_SIZE - self.chunk_len);
+        self.chunk[self.chunk_len..self.chunk_len + n].copy_from_slice(&buf[..n]);
+        self.chunk_len += n;
+        Ok(n)
+    }
+
+    /// Writes the buffered chunk if it is complete and flushes the inner writer.
+    ///
+    /// A partial chunk stays buffered: writing it early would produce a short chunk
+    /// in the middle of the stream, which Windows pads with zeros when decompressing.
+    fn flush(&mut self) -> io::Result<()> {
+        if self.chunk_len == CHUNK_SIZE {
+            self.write_chunk()?;
+        }
+        self.inner.flush()
+    }
+}
+
+/// Reads until `buf` is full or the reader is exhausted, returning the bytes read.
+fn read_fully<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Wraps a decompression error into an [`io::Error`].
+fn invalid_data(err: DecompressionError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
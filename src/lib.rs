@@ -31,14 +31,42 @@
 #![forbid(unsafe_code)]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
+pub mod chunk;
 pub mod compress;
 pub mod decompress;
 pub mod error;
+#[cfg(feature = "std")]
+pub mod io;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod report;
+pub mod salvage;
+pub mod sink;
+pub mod stream;
 
-pub use compress::compress;
-pub use decompress::decompress;
-pub use error::DecompressionError;
+pub use chunk::{Chunk, ChunkHeader, Chunks, chunks};
+pub use compress::{
+    CompressOptions, Compressor, Lznt1Context, RawChunks, compress, compress_into,
+    compress_to_sink, compress_with,
+};
+pub use decompress::{
+    DecompressOptions, DecompressStatus, ValidationLevel, decompress, decompress_fragment,
+    decompress_into, decompress_to_sink, decompress_with, decompress_with_status,
+};
+pub use error::{
+    CompressionError, DecompressionError, ErrorPosition, PartialDecompressionError, SinkFull,
+};
+#[cfg(feature = "std")]
+pub use io::{Lznt1Decoder, Lznt1Encoder};
+#[cfg(feature = "rayon")]
+pub use parallel::{par_compress, par_decompress};
+pub use report::{ChunkReport, CompressionReport, MatchStats, compress_with_report};
+pub use salvage::{DamagedChunk, SalvageFill, SalvageReport, decompress_salvage};
+pub use sink::{CountingSink, OutputSink, SliceSink};
+pub use stream::{Progress, StreamingDecompressor};
 
 #[cfg(test)]
 mod tests {
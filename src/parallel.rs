@@ -0,0 +1,131 @@
+//! Parallel compression and decompression with [`rayon`] (requires the `rayon`
+//! feature).
+
+use alloc::vec::Vec;
+
+use rayon::prelude::*;
+
+use crate::chunk::{Chunk, chunks};
+use crate::compress::{ChunkScratch, CompressOptions, Lznt1Context, compress_chunks};
+use crate::decompress::{CHUNK_SIZE, at_output_offset, decompress_chunk};
+use crate::error::DecompressionError;
+
+type Result<T> = core::result::Result<T, DecompressionError>;
+
+/// Number of chunks compressed by each parallel task, amortizing the per-task
+/// output allocation.
+const CHUNKS_PER_TASK: usize = 16;
+
+/// Compresses the entire input, compressing its chunks in parallel.
+///
+/// The input is split into runs of 4096-byte chunks that are compressed
+/// concurrently, each worker with its own [`Lznt1Context`], and the results are
+/// appended to `output` in order. Since chunks are compressed independently, the
+/// output is byte-identical to [`compress`](crate::compress()).
+///
+/// # Example
+///
+/// ```rust
+/// use lznt1::{compress, par_compress};
+///
+/// let input: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+/// let mut compressed = Vec::new();
+/// par_compress(&input, &mut compressed);
+///
+/// let mut expected = Vec::new();
+/// compress(&input, &mut expected);
+/// assert_eq!(compressed, expected);
+/// ```
+pub fn par_compress(input: &[u8], output: &mut Vec<u8>) {
+    let options = CompressOptions::new();
+    let parts: Vec<Vec<u8>> = input
+        .par_chunks(CHUNK_SIZE * CHUNKS_PER_TASK)
+        .map_init(
+            || (Lznt1Context::new(), ChunkScratch::new(&options)),
+            |(ctx, scratch), run| {
+                let mut part = Vec::new();
+                // Vectors grow as needed, so writing to one never fails.
+                let _ = compress_chunks(run, &mut part, ctx, scratch, &options);
+                part
+            },
+        )
+        .collect();
+
+    output.reserve(parts.iter().map(Vec::len).sum());
+    for part in parts {
+        output.extend_from_slice(&part);
+    }
+}
+
+/// Decompresses an entire LZNT1 stream, decoding its chunks in parallel.
+///
+/// LZNT1 chunks are independent, so the chunk headers are walked to find every
+/// chunk body, and the chunks are then decoded concurrently, each once, into
+/// consecutive 4096-byte slots of `output`. Every chunk but the last decompresses
+/// to exactly 4096 bytes in a well-formed stream, so the slots are already the
+/// final positions; otherwise the chunks following a short one are moved down to
+/// close the gap.
+///
+/// The result is byte-identical to [`decompress`](crate::decompress()), including
+/// the error returned for malformed input. On error, `output` is left unchanged.
+///
+/// # Example
+///
+/// ```rust
+/// use lznt1::{compress, par_decompress};
+///
+/// let input: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+/// let mut compressed = Vec::new();
+/// compress(&input, &mut compressed);
+///
+/// let mut output = Vec::new();
+/// par_decompress(&compressed, &mut output).unwrap();
+/// assert_eq!(output, input);
+/// ```
+pub fn par_decompress(input: &[u8], output: &mut Vec<u8>) -> Result<()> {
+    // A malformed header is only reported if every chunk before it is valid.
+    let mut list: Vec<Chunk<'_>> = Vec::new();
+    let mut header_error = None;
+    for chunk in chunks(input) {
+        match chunk {
+            Ok(chunk) => list.push(chunk),
+            Err(e) => header_error = Some(e),
+        }
+    }
+
+    let start = output.len();
+    output.resize(start + list.len() * CHUNK_SIZE, 0);
+
+    // Errors are reported at output offset 0 until the chunk's offset is known.
+    let sizes: Vec<Result<usize>> = list
+        .par_iter()
+        .zip(output[start..].par_chunks_mut(CHUNK_SIZE))
+        .map(|(chunk, slot)| {
+            decompress_chunk(chunk.body, chunk.header.compressed, slot, chunk.location(0))
+        })
+        .collect();
+
+    // Pack the chunks, reporting the first error in stream order.
+    let mut total = 0;
+    for (i, size) in sizes.into_iter().enumerate() {
+        let size = match size {
+            Ok(size) => size,
+            Err(e) => {
+                output.truncate(start);
+                return Err(at_output_offset(e, total));
+            }
+        };
+        let slot = start + i * CHUNK_SIZE;
+        if slot != start + total {
+            output.copy_within(slot..slot + size, start + total);
+        }
+        total += size;
+    }
+    output.truncate(start + total);
+
+    if let Some(e) = header_error {
+        output.truncate(start);
+        return Err(at_output_offset(e, total));
+    }
+    Ok(())
+}
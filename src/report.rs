@@ -0,0 +1,173 @@
+//! Per-chunk statistics about compressed output.
+//!
+//! [`compress_with_report`] compresses like [`compress_with`](crate::compress_with)
+//! and describes how well each chunk compressed, so compressibility can be logged
+//! without parsing the output again.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::chunk::ChunkHeader;
+use crate::compress::{ChunkScratch, CompressOptions, END_MARKER, Lznt1Context};
+use crate::decompress::CHUNK_SIZE;
+
+/// Counts of the items in compressed chunk bodies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct MatchStats {
+    /// Number of literal bytes.
+    pub literals: usize,
+    /// Number of offset/length tuples.
+    pub tuples: usize,
+    /// Total number of bytes produced by the tuples.
+    pub match_bytes: usize,
+    /// Largest tuple offset, or 0 if there is no tuple.
+    pub max_offset: usize,
+}
+
+impl MatchStats {
+    /// Returns the average tuple length, or 0 if there is no tuple.
+    #[must_use]
+    pub fn average_match_len(&self) -> f64 {
+        if self.tuples == 0 {
+            return 0.0;
+        }
+        self.match_bytes as f64 / self.tuples as f64
+    }
+
+    /// Statistics with every count at zero.
+    pub(crate) const EMPTY: Self = Self {
+        literals: 0,
+        tuples: 0,
+        match_bytes: 0,
+        max_offset: 0,
+    };
+
+    /// Counts a literal byte.
+    pub(crate) const fn record_literal(&mut self) {
+        self.literals += 1;
+    }
+
+    /// Counts a tuple copying `length` bytes from `offset` bytes back.
+    pub(crate) const fn record_tuple(&mut self, offset: usize, length: usize) {
+        self.tuples += 1;
+        self.match_bytes += length;
+        if offset > self.max_offset {
+            self.max_offset = offset;
+        }
+    }
+
+    /// Adds the counts of `other` to these.
+    const fn add(&mut self, other: &Self) {
+        self.literals += other.literals;
+        self.tuples += other.tuples;
+        self.match_bytes += other.match_bytes;
+        if other.max_offset > self.max_offset {
+            self.max_offset = other.max_offset;
+        }
+    }
+}
+
+/// Statistics about one chunk, as reported by [`compress_with_report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkReport {
+    /// Input bytes covered by the chunk.
+    pub input_range: Range<usize>,
+    /// Output bytes of the chunk, header included, relative to the start of the
+    /// compressed data.
+    pub output_range: Range<usize>,
+    /// Whether the chunk was stored raw, as decided by the
+    /// [`RawChunks`](crate::RawChunks) policy: with the default policy, because
+    /// compressing it did not save [`min_savings`](crate::CompressOptions::min_savings)
+    /// bytes.
+    pub raw: bool,
+    /// Items of the compressed body (all zero for raw chunks).
+    pub stats: MatchStats,
+}
+
+/// Statistics about a compressed stream, as returned by [`compress_with_report`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CompressionReport {
+    /// Chunks, in stream order.
+    pub chunks: Vec<ChunkReport>,
+    /// Number of input bytes.
+    pub input_len: usize,
+    /// Number of compressed bytes, headers and end-of-stream marker included.
+    pub output_len: usize,
+    /// Number of chunks stored raw.
+    pub raw_chunks: usize,
+    /// Items of every compressed chunk body.
+    pub totals: MatchStats,
+}
+
+impl CompressionReport {
+    /// Returns the compressed size as a fraction of the input size, or 0 for an
+    /// empty input.
+    #[must_use]
+    pub fn ratio(&self) -> f64 {
+        if self.input_len == 0 {
+            return 0.0;
+        }
+        self.output_len as f64 / self.input_len as f64
+    }
+}
+
+/// Compresses the entire input like [`compress_with`](crate::compress_with),
+/// returning statistics about every chunk.
+///
+/// The compressed bytes are identical to those of `compress_with`; the statistics
+/// are counted by the encoder as it emits each literal and tuple, so the output is
+/// never parsed again.
+///
+/// # Example
+///
+/// ```rust
+/// use lznt1::{CompressOptions, compress_with_report};
+///
+/// let mut input = b"abcd".repeat(1024);
+/// input.extend(0..=255u8); // No repeated byte sequence: stored raw
+///
+/// let mut compressed = Vec::new();
+/// let report = compress_with_report(&input, &mut compressed, &CompressOptions::new());
+/// assert_eq!(report.output_len, compressed.len());
+/// assert!(!report.chunks[0].raw);
+/// assert!(report.chunks[0].stats.average_match_len() > 100.0);
+/// assert!(report.chunks[1].raw);
+/// ```
+pub fn compress_with_report(
+    input: &[u8],
+    output: &mut Vec<u8>,
+    options: &CompressOptions,
+) -> CompressionReport {
+    let start = output.len();
+    let mut ctx = Lznt1Context::new();
+    let mut scratch = ChunkScratch::new(options);
+    let mut report = CompressionReport {
+        input_len: input.len(),
+        ..CompressionReport::default()
+    };
+
+    for (index, chunk) in input.chunks(CHUNK_SIZE).enumerate() {
+        let (header, body, stats) = scratch.encode(chunk, &mut ctx, options);
+        let raw = ChunkHeader::parse(u16::from_le_bytes(header)).is_some_and(|h| !h.compressed);
+
+        let output_start = output.len() - start;
+        output.extend_from_slice(&header);
+        output.extend_from_slice(body);
+
+        let input_start = index * CHUNK_SIZE;
+        report.chunks.push(ChunkReport {
+            input_range: input_start..input_start + chunk.len(),
+            output_range: output_start..output.len() - start,
+            raw,
+            stats,
+        });
+        report.raw_chunks += usize::from(raw);
+        report.totals.add(&stats);
+    }
+    if options.end_marker {
+        output.extend_from_slice(&END_MARKER);
+    }
+
+    report.output_len = output.len() - start;
+    report
+}
@@ -0,0 +1,195 @@
+//! Best-effort recovery of damaged LZNT1 streams.
+//!
+//! [`decompress_salvage`] decodes as much of a corrupted stream as possible:
+//! unreadable chunks are replaced by a fill of 4096 bytes, reported, and decoding
+//! resumes at the next plausible chunk header.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::chunk::ChunkHeader;
+use crate::decompress::{CHUNK_SIZE, ChunkLocation, decompress_chunk_partial};
+use crate::error::{DecompressionError, ErrorPosition};
+
+/// What [`decompress_salvage`] writes in place of a damaged chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SalvageFill {
+    /// 4096 zero bytes.
+    #[default]
+    Zeros,
+    /// The bytes decoded before the failure, padded with zeros to 4096 bytes.
+    Partial,
+}
+
+/// A damaged region of the input, as reported by [`decompress_salvage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DamagedChunk {
+    /// Zero-based index of the chunk, counting each damaged region as one chunk.
+    pub index: usize,
+    /// Input bytes skipped, from the damaged chunk header up to the point where
+    /// decoding resumed.
+    pub input_range: Range<usize>,
+    /// The error that made the chunk unreadable.
+    pub error: DecompressionError,
+}
+
+/// Damage found by [`decompress_salvage`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SalvageReport {
+    /// Damaged chunks, in stream order.
+    pub damaged: Vec<DamagedChunk>,
+}
+
+impl SalvageReport {
+    /// Returns `true` if the stream was decoded without any damage.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.damaged.is_empty()
+    }
+}
+
+/// Decompresses a possibly corrupted LZNT1 stream, skipping damaged chunks.
+///
+/// Chunks are decoded as by [`decompress`](crate::decompress()), so a header with
+/// wrong signature bits is accepted as long as its body decodes. When a chunk
+/// cannot be read (an invalid back-reference, a body running past the input, ...),
+/// `fill` is written in its place, so that the following chunks stay at their
+/// 4KB-aligned positions, and the failure is recorded in the returned report.
+///
+/// Decoding then resumes at the end of the damaged chunk if a plausible chunk
+/// header or the end of the stream follows it, or otherwise at the next offset
+/// holding a plausible chunk header: a valid signature, a body within the input,
+/// and either another plausible header, an end-of-stream marker or the end of the
+/// input right after it. Since the number of chunks lost in a skipped region is
+/// unknown, each region is replaced by a single fill.
+///
+/// # Example
+///
+/// ```rust
+/// use lznt1::{SalvageFill, compress, decompress_salvage};
+///
+/// let mut compressed = Vec::new();
+/// compress(&[b'A'; 8192], &mut compressed);
+/// compressed[5] = 0xFF; // Make the first chunk's tuple reach before the chunk
+///
+/// let mut output = Vec::new();
+/// let report = decompress_salvage(&compressed, &mut output, SalvageFill::Zeros);
+/// assert_eq!(report.damaged.len(), 1);
+/// assert_eq!(output.len(), 8192);
+/// assert!(output[4096..].iter().all(|&b| b == b'A'));
+/// ```
+pub fn decompress_salvage(input: &[u8], output: &mut Vec<u8>, fill: SalvageFill) -> SalvageReport {
+    let start_out_len = output.len();
+    let mut report = SalvageReport::default();
+    let mut window = [0u8; CHUNK_SIZE];
+    let mut pos = 0;
+    let mut index = 0;
+
+    while !at_stream_end(input, pos) {
+        let header_pos = ErrorPosition {
+            input_offset: pos,
+            chunk_index: index,
+            chunk_output_offset: output.len() - start_out_len,
+        };
+        let location = ChunkLocation {
+            body_offset: pos + ChunkHeader::SIZE,
+            index,
+            output_offset: header_pos.chunk_output_offset,
+        };
+
+        let (produced, error, next) = match read_header(input, pos) {
+            // A single non-zero byte is left at the end of the input.
+            None => (
+                0,
+                DecompressionError::UnexpectedEof {
+                    position: header_pos,
+                },
+                input.len(),
+            ),
+            Some(header) => {
+                let body_end = location.body_offset + header.body_len;
+                if body_end > input.len() {
+                    // Decode whatever is left of the body before the next plausible chunk.
+                    let next = resync(input, pos + 1);
+                    let body = input.get(location.body_offset..next).unwrap_or_default();
+                    let (produced, _) =
+                        decompress_chunk_partial(body, header.compressed, &mut window, location);
+                    let error = DecompressionError::InputTooShort {
+                        position: header_pos,
+                    };
+                    (produced, error, next)
+                } else {
+                    let body = &input[location.body_offset..body_end];
+                    match decompress_chunk_partial(body, header.compressed, &mut window, location) {
+                        (produced, Ok(())) => {
+                            output.extend_from_slice(&window[..produced]);
+                            pos = body_end;
+                            index += 1;
+                            continue;
+                        }
+                        (produced, Err(error)) => {
+                            // The body is damaged, but its header may still be trusted.
+                            let next = if is_boundary(input, body_end) {
+                                body_end
+                            } else {
+                                resync(input, pos + 1)
+                            };
+                            (produced, error, next)
+                        }
+                    }
+                }
+            }
+        };
+
+        let kept = match fill {
+            SalvageFill::Zeros => 0,
+            SalvageFill::Partial => produced,
+        };
+        output.extend_from_slice(&window[..kept]);
+        output.resize(output.len() + CHUNK_SIZE - kept, 0);
+
+        report.damaged.push(DamagedChunk {
+            index,
+            input_range: pos..next,
+            error,
+        });
+        pos = next;
+        index += 1;
+    }
+
+    report
+}
+
+/// Returns `true` if the stream ends at `pos`: at the end of the input, at an
+/// end-of-stream marker or at a single trailing null byte.
+fn at_stream_end(input: &[u8], pos: usize) -> bool {
+    matches!(&input[pos..], [] | [0] | [0, 0, ..])
+}
+
+/// Reads the chunk header at `pos`, or `None` if it is truncated or an
+/// end-of-stream marker.
+fn read_header(input: &[u8], pos: usize) -> Option<ChunkHeader> {
+    let bytes = input.get(pos..pos + ChunkHeader::SIZE)?;
+    ChunkHeader::parse(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+/// Returns the end of the chunk at `pos` if its header is plausible: a valid
+/// signature and a body lying within the input.
+fn plausible_chunk_end(input: &[u8], pos: usize) -> Option<usize> {
+    let header = read_header(input, pos)?;
+    let end = pos + ChunkHeader::SIZE + header.body_len;
+    (header.has_valid_signature() && end <= input.len()).then_some(end)
+}
+
+/// Returns `true` if the stream ends at `pos` or a plausible chunk starts there.
+fn is_boundary(input: &[u8], pos: usize) -> bool {
+    at_stream_end(input, pos) || plausible_chunk_end(input, pos).is_some()
+}
+
+/// Finds the first offset from `from` holding a plausible chunk followed by
+/// another boundary, or the end of the input if there is none.
+fn resync(input: &[u8], from: usize) -> usize {
+    (from..input.len())
+        .find(|&pos| plausible_chunk_end(input, pos).is_some_and(|end| is_boundary(input, end)))
+        .unwrap_or(input.len())
+}
@@ -0,0 +1,231 @@
+//! Destinations for compressed and decompressed bytes.
+//!
+//! [`OutputSink`] lets [`decompress_to_sink`](crate::decompress_to_sink) and
+//! [`compress_to_sink`](crate::compress_to_sink) write to any container, not only
+//! a [`Vec<u8>`]. Implementations are provided for vectors, fixed buffers
+//! ([`SliceSink`]) and byte counters ([`CountingSink`]).
+
+use alloc::vec::Vec;
+
+use crate::error::SinkFull;
+
+/// An append-only destination for bytes.
+///
+/// Decompression resolves back-references with [`copy_within_history`], so a sink
+/// used for decompression must retain at least the bytes of the chunk being
+/// decoded (up to 4096 bytes), or everything written when strict chunk-local
+/// validation is disabled. Sinks that only observe the data, such as
+/// [`CountingSink`], may ignore the copied bytes.
+///
+/// [`copy_within_history`]: OutputSink::copy_within_history
+pub trait OutputSink {
+    /// Returns the number of bytes written so far.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if nothing has been written yet.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends a single byte.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SinkFull`] if the sink cannot hold another byte.
+    fn push(&mut self, byte: u8) -> Result<(), SinkFull>;
+
+    /// Appends a slice of bytes, either entirely or not at all.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SinkFull`] if the sink cannot hold `bytes.len()` more bytes.
+    fn extend_from_slice(&mut self, bytes: &[u8]) -> Result<(), SinkFull>;
+
+    /// Appends `length` bytes copied from `offset` bytes back in the output.
+    ///
+    /// The caller guarantees `1 <= offset <= self.len()`. Source and destination
+    /// may overlap, in which case freshly copied bytes are repeated.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SinkFull`] if the sink cannot hold `length` more bytes.
+    fn copy_within_history(&mut self, offset: usize, length: usize) -> Result<(), SinkFull>;
+}
+
+impl OutputSink for Vec<u8> {
+    #[inline]
+    fn len(&self) -> usize {
+        Self::len(self)
+    }
+
+    #[inline]
+    fn push(&mut self, byte: u8) -> Result<(), SinkFull> {
+        Self::push(self, byte);
+        Ok(())
+    }
+
+    #[inline]
+    fn extend_from_slice(&mut self, bytes: &[u8]) -> Result<(), SinkFull> {
+        Self::extend_from_slice(self, bytes);
+        Ok(())
+    }
+
+    #[inline]
+    fn copy_within_history(&mut self, offset: usize, length: usize) -> Result<(), SinkFull> {
+        self.reserve(length);
+
+        // --- RLE Fast Path (Offset == 1) ---
+        if offset == 1 {
+            let last_byte = self[Self::len(self) - 1];
+            self.resize(Self::len(self) + length, last_byte);
+        } else {
+            // Standard LZ77 Copy (supports overlapping ranges)
+            let src_pos = Self::len(self) - offset;
+            for k in 0..length {
+                let val = self[src_pos + k];
+                Self::push(self, val);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// An [`OutputSink`] filling a fixed buffer from the start.
+#[derive(Debug)]
+pub struct SliceSink<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> SliceSink<'a> {
+    /// Creates a sink writing to the start of `buf`.
+    pub const fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// Returns the bytes written so far.
+    #[must_use]
+    pub fn written(&self) -> &[u8] {
+        &self.buf[..self.pos]
+    }
+
+    /// Reserves `additional` bytes, returning the start of the reserved range.
+    #[inline]
+    const fn advance(&mut self, additional: usize) -> Result<usize, SinkFull> {
+        if additional > self.buf.len() - self.pos {
+            return Err(SinkFull);
+        }
+        let start = self.pos;
+        self.pos += additional;
+        Ok(start)
+    }
+}
+
+impl OutputSink for SliceSink<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.pos
+    }
+
+    #[inline]
+    fn push(&mut self, byte: u8) -> Result<(), SinkFull> {
+        let at = self.advance(1)?;
+        self.buf[at] = byte;
+        Ok(())
+    }
+
+    #[inline]
+    fn extend_from_slice(&mut self, bytes: &[u8]) -> Result<(), SinkFull> {
+        let at = self.advance(bytes.len())?;
+        self.buf[at..at + bytes.len()].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    #[inline]
+    fn copy_within_history(&mut self, offset: usize, length: usize) -> Result<(), SinkFull> {
+        let at = self.advance(length)?;
+        let src_pos = at - offset;
+
+        if offset == 1 {
+            let last_byte = self.buf[src_pos];
+            self.buf[at..at + length].fill(last_byte);
+        } else if offset >= length {
+            self.buf.copy_within(src_pos..src_pos + length, at);
+        } else {
+            // Overlapping copy must proceed byte by byte.
+            for k in 0..length {
+                self.buf[at + k] = self.buf[src_pos + k];
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// An [`OutputSink`] that counts bytes without storing them, up to a limit.
+///
+/// Useful to compute the size of an output before allocating it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CountingSink {
+    count: usize,
+    limit: usize,
+}
+
+impl Default for CountingSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CountingSink {
+    /// Creates an unbounded counter.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::with_limit(usize::MAX)
+    }
+
+    /// Creates a counter that reports [`SinkFull`] rather than exceeding `limit` bytes.
+    #[must_use]
+    pub const fn with_limit(limit: usize) -> Self {
+        Self { count: 0, limit }
+    }
+
+    /// Returns the number of bytes counted so far.
+    #[must_use]
+    pub const fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Counts `additional` bytes.
+    #[inline]
+    const fn add(&mut self, additional: usize) -> Result<(), SinkFull> {
+        if additional > self.limit - self.count {
+            return Err(SinkFull);
+        }
+        self.count += additional;
+        Ok(())
+    }
+}
+
+impl OutputSink for CountingSink {
+    #[inline]
+    fn len(&self) -> usize {
+        self.count
+    }
+
+    #[inline]
+    fn push(&mut self, _byte: u8) -> Result<(), SinkFull> {
+        self.add(1)
+    }
+
+    #[inline]
+    fn extend_from_slice(&mut self, bytes: &[u8]) -> Result<(), SinkFull> {
+        self.add(bytes.len())
+    }
+
+    #[inline]
+    fn copy_within_history(&mut self, _offset: usize, length: usize) -> Result<(), SinkFull> {
+        self.add(length)
+    }
+}
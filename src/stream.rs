@@ -0,0 +1,427 @@
+//! Push-based incremental decompression for `no_std` environments.
+//!
+//! [`StreamingDecompressor`] accepts compressed data in arbitrarily sized pieces
+//! (down to single bytes) and resumes exactly where the previous piece ended,
+//! including in the middle of a chunk header, a tag group or a match tuple.
+
+use crate::chunk::ChunkHeader;
+use crate::decompress::{
+    CHUNK_SIZE, INITIAL_SPLIT, INITIAL_THRESHOLD, TAG_GROUP_SIZE, update_adaptive_state,
+};
+use crate::error::{DecompressionError, ErrorPosition};
+
+type Result<T> = core::result::Result<T, DecompressionError>;
+
+/// Summary of a single [`StreamingDecompressor::feed`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Progress {
+    /// Number of input bytes consumed.
+    ///
+    /// This equals the input length unless the end-of-stream marker was reached,
+    /// in which case the bytes following the marker are left unconsumed.
+    pub consumed: usize,
+    /// Number of decompressed bytes passed to the output callback.
+    pub produced: usize,
+    /// Whether the `0x0000` end-of-stream marker has been reached.
+    pub finished: bool,
+}
+
+/// Position of the decoder within the compressed stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Expecting a chunk header; `low` holds its first byte once read.
+    Header { low: Option<u8> },
+    /// Inside a raw chunk with `remaining` body bytes left.
+    Raw { remaining: usize },
+    /// Expecting the tag byte of the next tag group.
+    Tag { remaining: usize },
+    /// Expecting item `index` of the tag group described by `tag`.
+    Item {
+        tag: u8,
+        index: usize,
+        remaining: usize,
+    },
+    /// Expecting the second byte of a match tuple whose first byte is `low`.
+    TupleHigh {
+        tag: u8,
+        index: usize,
+        low: u8,
+        remaining: usize,
+    },
+    /// The end-of-stream marker has been consumed.
+    Done,
+}
+
+/// An incremental LZNT1 decompressor driven by pushing input into it.
+///
+/// Unlike [`decompress`](crate::decompress()), this type never needs the whole stream
+/// in memory: feed it packets as they arrive and it emits decompressed bytes
+/// through a callback. Its memory use is fixed at one 4KB chunk window, which holds
+/// the history for back-references. References are therefore resolved within the
+/// current chunk, and a chunk expanding beyond 4096 bytes is rejected with
+/// [`DecompressionError::ChunkOverflow`].
+///
+/// After an error, every later [`feed`](Self::feed) and [`finish`](Self::finish)
+/// returns the same error until the decompressor is [`reset`](Self::reset).
+///
+/// # Example
+///
+/// ```rust
+/// use lznt1::{StreamingDecompressor, compress};
+///
+/// let mut compressed = Vec::new();
+/// compress(b"abcabcabcabcabcabcabc", &mut compressed);
+///
+/// let mut decoder = StreamingDecompressor::new();
+/// let mut output = Vec::new();
+/// for packet in compressed.chunks(3) {
+///     decoder.feed(packet, |bytes| output.extend_from_slice(bytes)).unwrap();
+/// }
+/// decoder.finish().unwrap();
+/// assert_eq!(output, b"abcabcabcabcabcabcabc");
+/// ```
+pub struct StreamingDecompressor {
+    state: State,
+    /// The error that stopped decoding, returned again by every later call.
+    failure: Option<DecompressionError>,
+
+    // Adaptive state of the current compressed chunk.
+    split: usize,
+    mask: usize,
+    threshold: usize,
+
+    /// Decompressed bytes of the current compressed chunk.
+    window: [u8; CHUNK_SIZE],
+    window_len: usize,
+    /// Number of window bytes already passed to the output callback.
+    emitted: usize,
+
+    // Stream position, used to report error locations.
+    input_offset: usize,
+    output_offset: usize,
+    chunk_index: usize,
+    chunk_input_offset: usize,
+    chunk_output_offset: usize,
+}
+
+impl Default for StreamingDecompressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamingDecompressor {
+    /// Creates a decompressor positioned at the start of a stream.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            state: State::Header { low: None },
+            failure: None,
+            split: INITIAL_SPLIT,
+            mask: (1 << INITIAL_SPLIT) - 1,
+            threshold: INITIAL_THRESHOLD,
+            window: [0; CHUNK_SIZE],
+            window_len: 0,
+            emitted: 0,
+            input_offset: 0,
+            output_offset: 0,
+            chunk_index: 0,
+            chunk_input_offset: 0,
+            chunk_output_offset: 0,
+        }
+    }
+
+    /// Resets the decompressor so that it can decode a new stream.
+    pub const fn reset(&mut self) {
+        self.state = State::Header { low: None };
+        self.failure = None;
+        self.window_len = 0;
+        self.emitted = 0;
+        self.input_offset = 0;
+        self.output_offset = 0;
+        self.chunk_index = 0;
+        self.chunk_input_offset = 0;
+        self.chunk_output_offset = 0;
+    }
+
+    /// Returns `true` once the `0x0000` end-of-stream marker has been consumed.
+    #[must_use]
+    pub const fn is_finished(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+
+    /// Decompresses the next piece of the stream.
+    ///
+    /// Decompressed bytes are passed to `out` in order, possibly across several
+    /// invocations per call. Bytes decoded before an error are still emitted.
+    pub fn feed<F: FnMut(&[u8])>(&mut self, input: &[u8], mut out: F) -> Result<Progress> {
+        if let Some(err) = &self.failure {
+            return Err(err.clone());
+        }
+
+        let mut progress = Progress::default();
+        let result = self.run(input, &mut progress, &mut out);
+        self.flush(&mut progress, &mut out);
+        self.input_offset += progress.consumed;
+        progress.finished = self.is_finished();
+        if let Err(err) = &result {
+            self.failure = Some(err.clone());
+        }
+        result.map(|()| progress)
+    }
+
+    /// Signals the end of the input.
+    ///
+    /// Returns an error if the stream was truncated inside a chunk header or body.
+    /// A stream ending on a chunk boundary, at the end-of-stream marker or with a
+    /// single trailing null byte is accepted. The decompressor is reset afterwards,
+    /// unless an earlier call failed.
+    pub fn finish(&mut self) -> Result<()> {
+        if let Some(err) = &self.failure {
+            return Err(err.clone());
+        }
+
+        let result = match self.state {
+            State::Header {
+                low: None | Some(0),
+            }
+            | State::Done => Ok(()),
+            State::Header { low: Some(_) } => Err(DecompressionError::UnexpectedEof {
+                position: self.position(self.input_offset - 1),
+            }),
+            _ => Err(DecompressionError::InputTooShort {
+                position: self.position(self.chunk_input_offset),
+            }),
+        };
+        self.reset();
+        result
+    }
+
+    /// Returns the error position of the stream byte at `input_offset`.
+    const fn position(&self, input_offset: usize) -> ErrorPosition {
+        ErrorPosition {
+            input_offset,
+            chunk_index: self.chunk_index,
+            chunk_output_offset: self.chunk_output_offset,
+        }
+    }
+
+    /// Advances the state machine until the input is exhausted or the stream ends.
+    ///
+    /// `progress.consumed` serves as the read cursor into `input`.
+    fn run<F: FnMut(&[u8])>(
+        &mut self,
+        input: &[u8],
+        progress: &mut Progress,
+        out: &mut F,
+    ) -> Result<()> {
+        loop {
+            // Stream offset of the next unread byte.
+            let offset = self.input_offset + progress.consumed;
+            let next = input.get(progress.consumed).copied();
+
+            match self.state {
+                State::Done => break,
+                State::Header { low: None } => {
+                    let Some(byte) = next else { break };
+                    progress.consumed += 1;
+                    self.chunk_input_offset = offset;
+                    self.chunk_output_offset = self.output_offset;
+                    self.state = State::Header { low: Some(byte) };
+                }
+                State::Header { low: Some(low) } => {
+                    let Some(high) = next else { break };
+                    progress.consumed += 1;
+                    let Some(header) = ChunkHeader::parse(u16::from_le_bytes([low, high])) else {
+                        self.state = State::Done; // Standard End-of-Stream marker
+                        break;
+                    };
+
+                    self.state = if header.compressed {
+                        self.split = INITIAL_SPLIT;
+                        self.mask = (1 << INITIAL_SPLIT) - 1;
+                        self.threshold = INITIAL_THRESHOLD;
+                        State::Tag {
+                            remaining: header.body_len,
+                        }
+                    } else {
+                        State::Raw {
+                            remaining: header.body_len,
+                        }
+                    };
+                }
+                State::Raw { remaining } => {
+                    // Raw bodies are passed through without touching the window.
+                    let rest = &input[progress.consumed..];
+                    if rest.is_empty() {
+                        break;
+                    }
+                    let n = remaining.min(rest.len());
+                    out(&rest[..n]);
+                    progress.consumed += n;
+                    progress.produced += n;
+                    self.output_offset += n;
+
+                    if n == remaining {
+                        self.chunk_index += 1;
+                        self.state = State::Header { low: None };
+                    } else {
+                        self.state = State::Raw {
+                            remaining: remaining - n,
+                        };
+                    }
+                }
+                State::Tag { remaining } => {
+                    let Some(tag) = next else { break };
+                    progress.consumed += 1;
+                    self.state = State::Item {
+                        tag,
+                        index: 0,
+                        remaining: remaining - 1,
+                    };
+                }
+                State::Item {
+                    tag,
+                    index,
+                    remaining,
+                } => {
+                    let is_link = (tag >> index) & 1 != 0;
+
+                    // Only reachable directly after a tag byte that ends the chunk.
+                    if remaining == 0 {
+                        if is_link {
+                            return Err(DecompressionError::UnexpectedEof {
+                                position: self.position(offset),
+                            });
+                        }
+                        self.end_chunk(progress, out);
+                        continue;
+                    }
+                    if is_link && remaining < 2 {
+                        return Err(DecompressionError::UnexpectedEof {
+                            position: self.position(offset),
+                        });
+                    }
+
+                    let Some(byte) = next else { break };
+                    progress.consumed += 1;
+                    if is_link {
+                        self.state = State::TupleHigh {
+                            tag,
+                            index,
+                            low: byte,
+                            remaining: remaining - 1,
+                        };
+                    } else {
+                        if self.window_len == CHUNK_SIZE {
+                            return Err(DecompressionError::ChunkOverflow {
+                                position: self.position(offset),
+                            });
+                        }
+                        self.window[self.window_len] = byte;
+                        self.window_len += 1;
+                        self.next_item(tag, index, remaining - 1, progress, out);
+                    }
+                }
+                State::TupleHigh {
+                    tag,
+                    index,
+                    low,
+                    remaining,
+                } => {
+                    let Some(high) = next else { break };
+                    progress.consumed += 1;
+                    let tuple = u16::from_le_bytes([low, high]) as usize;
+
+                    // Decode Length/Offset using current adaptive split
+                    let length = (tuple & self.mask) + 3;
+                    let match_offset = (tuple >> self.split) + 1;
+                    self.apply_match(length, match_offset)
+                        .map_err(|e| e.with_position(self.position(offset - 1)))?;
+                    self.next_item(tag, index, remaining - 1, progress, out);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Completes an item: updates the adaptive state and moves to the next item.
+    fn next_item<F: FnMut(&[u8])>(
+        &mut self,
+        tag: u8,
+        index: usize,
+        remaining: usize,
+        progress: &mut Progress,
+        out: &mut F,
+    ) {
+        update_adaptive_state(
+            self.window_len,
+            &mut self.threshold,
+            &mut self.split,
+            &mut self.mask,
+        );
+
+        if remaining == 0 {
+            self.end_chunk(progress, out);
+        } else if index + 1 == TAG_GROUP_SIZE {
+            self.state = State::Tag { remaining };
+        } else {
+            self.state = State::Item {
+                tag,
+                index: index + 1,
+                remaining,
+            };
+        }
+    }
+
+    /// Copies a back-reference within the chunk window.
+    ///
+    /// Errors carry a placeholder position, which the caller replaces.
+    fn apply_match(&mut self, length: usize, offset: usize) -> Result<()> {
+        if offset > self.window_len {
+            return Err(DecompressionError::InvalidOffset {
+                position: ErrorPosition::UNKNOWN,
+                offset,
+                available: self.window_len,
+            });
+        }
+        if length > CHUNK_SIZE - self.window_len {
+            return Err(DecompressionError::ChunkOverflow {
+                position: ErrorPosition::UNKNOWN,
+            });
+        }
+
+        let src_pos = self.window_len - offset;
+        if offset >= length {
+            self.window
+                .copy_within(src_pos..src_pos + length, self.window_len);
+        } else {
+            // Overlapping copy must proceed byte by byte.
+            for k in 0..length {
+                self.window[self.window_len + k] = self.window[src_pos + k];
+            }
+        }
+        self.window_len += length;
+        Ok(())
+    }
+
+    /// Emits the rest of the current chunk and prepares for the next header.
+    fn end_chunk<F: FnMut(&[u8])>(&mut self, progress: &mut Progress, out: &mut F) {
+        self.flush(progress, out);
+        self.window_len = 0;
+        self.emitted = 0;
+        self.chunk_index += 1;
+        self.state = State::Header { low: None };
+    }
+
+    /// Passes window bytes not yet emitted to the output callback.
+    fn flush<F: FnMut(&[u8])>(&mut self, progress: &mut Progress, out: &mut F) {
+        if self.window_len > self.emitted {
+            out(&self.window[self.emitted..self.window_len]);
+            progress.produced += self.window_len - self.emitted;
+            self.output_offset += self.window_len - self.emitted;
+            self.emitted = self.window_len;
+        }
+    }
+}
@@ -1,4 +1,11 @@
-use lznt1::{DecompressionError, compress, decompress};
+use lznt1::{
+    ChunkHeader, CompressOptions, CompressionError, Compressor, CountingSink, DecompressOptions,
+    DecompressStatus, DecompressionError, ErrorPosition, Lznt1Context, OutputSink, RawChunks,
+    SalvageFill, SinkFull, SliceSink, StreamingDecompressor, ValidationLevel, chunks, compress,
+    compress_into, compress_to_sink, compress_with, compress_with_report, decompress,
+    decompress_fragment, decompress_into, decompress_salvage, decompress_to_sink, decompress_with,
+    decompress_with_status,
+};
 
 // --- Test Constants ---
 
@@ -6,8 +13,6 @@ use lznt1::{DecompressionError, compress, decompress};
 const HEADER_COMPRESSED: u16 = 0xB000;
 /// Header flag indicating raw (uncompressed) data (0x3000).
 const HEADER_UNCOMPRESSED: u16 = 0x3000;
-/// Mask to extract the chunk size (lower 12 bits).
-const SIZE_MASK: u16 = 0x0FFF;
 
 // --- Helpers ---
 
@@ -40,10 +45,32 @@ fn parse_header(data: &[u8]) -> (bool, usize) {
         data.len() >= 2,
         "Compressed data too short to contain a header"
     );
-    let val = u16::from_le_bytes([data[0], data[1]]);
-    let is_compressed = (val & 0x8000) != 0;
-    let size = ((val & SIZE_MASK) + 1) as usize;
-    (is_compressed, size)
+    let header = ChunkHeader::parse(u16::from_le_bytes([data[0], data[1]]))
+        .expect("Unexpected end-of-stream marker");
+    (header.compressed, header.body_len)
+}
+
+/// Builds a single compressed chunk that expands to 4099 bytes:
+/// a literal `A` followed by an offset-1 match of the maximum length (4098).
+fn oversized_chunk() -> Vec<u8> {
+    let header = HEADER_COMPRESSED | 3; // Size 4
+    let mut data = header.to_le_bytes().to_vec();
+    data.push(0b10); // Tag: Literal, then Ref
+    data.push(b'A');
+    // Offset 1, Length 4098 (12-bit length field saturated).
+    data.extend_from_slice(&0x0FFFu16.to_le_bytes());
+    data
+}
+
+/// Helper to decompress a stream with `StreamingDecompressor`, feeding `packet` bytes at a time.
+fn stream_decompress(data: &[u8], packet: usize) -> Result<Vec<u8>, DecompressionError> {
+    let mut decoder = StreamingDecompressor::new();
+    let mut out = Vec::new();
+    for piece in data.chunks(packet) {
+        decoder.feed(piece, |bytes| out.extend_from_slice(bytes))?;
+    }
+    decoder.finish()?;
+    Ok(out)
 }
 
 // --- Basic Sanity & Boundaries (Tests 1-7) ---
@@ -248,10 +275,10 @@ fn t20_tag_byte_mixed() {
 fn t21_decompress_unexpected_eof_header() {
     let data = vec![0xB0]; // Only 1 byte
     let mut out = Vec::new();
-    assert_eq!(
+    assert!(matches!(
         decompress(&data, &mut out),
-        Err(DecompressionError::UnexpectedEof)
-    );
+        Err(DecompressionError::UnexpectedEof { .. })
+    ));
 }
 
 /// Test: Header claims size larger than available input.
@@ -260,10 +287,10 @@ fn t22_decompress_input_too_short_header() {
     let header = HEADER_COMPRESSED | 0b110_0011; // Size 100
     let data = header.to_le_bytes();
     let mut out = Vec::new();
-    assert_eq!(
+    assert!(matches!(
         decompress(&data, &mut out),
-        Err(DecompressionError::InputTooShort)
-    );
+        Err(DecompressionError::InputTooShort { .. })
+    ));
 }
 
 /// Test: Unexpected EOF inside a compressed tag group.
@@ -275,10 +302,10 @@ fn t23_decompress_unexpected_eof_in_tag_group() {
     data.push(b'A'); // Only 1 provided
 
     let mut out = Vec::new();
-    assert_eq!(
+    assert!(matches!(
         decompress(&data, &mut out),
-        Err(DecompressionError::InputTooShort)
-    );
+        Err(DecompressionError::InputTooShort { .. })
+    ));
 }
 
 /// Test: Invalid offset (pointing before start of buffer).
@@ -293,10 +320,10 @@ fn t24_decompress_invalid_offset_start() {
     data.extend_from_slice(&0x0000u16.to_le_bytes());
 
     let mut out = Vec::new();
-    assert_eq!(
+    assert!(matches!(
         decompress(&data, &mut out),
-        Err(DecompressionError::InvalidOffset)
-    );
+        Err(DecompressionError::InvalidOffset { .. })
+    ));
 }
 
 /// Test: Stream clean termination via null header (0x0000).
@@ -316,10 +343,10 @@ fn t26_decompress_truncated_tuple() {
     data.push(0x00); // 1st byte of tuple (missing 2nd)
 
     let mut out = Vec::new();
-    assert_eq!(
+    assert!(matches!(
         decompress(&data, &mut out),
-        Err(DecompressionError::UnexpectedEof)
-    );
+        Err(DecompressionError::UnexpectedEof { .. })
+    ));
 }
 
 /// Test: Offset boundary check (Offset > Output Length).
@@ -333,10 +360,10 @@ fn t27_offset_boundary_check() {
     // Offset=10 (fail), Len=3. Tuple = ((9)<<12) | 0 = 0x9000
     chunk.extend_from_slice(&0x9000u16.to_le_bytes());
 
-    assert_eq!(
+    assert!(matches!(
         decompress(&chunk, &mut input),
-        Err(DecompressionError::InvalidOffset)
-    );
+        Err(DecompressionError::InvalidOffset { .. })
+    ));
 }
 
 /// Test: Valid large backward reference.
@@ -356,10 +383,10 @@ fn t29_header_uncompressed_length_mismatch() {
     data.push(0xAA); // Missing 3 bytes
 
     let mut out = Vec::new();
-    assert_eq!(
+    assert!(matches!(
         decompress(&data, &mut out),
-        Err(DecompressionError::InputTooShort)
-    );
+        Err(DecompressionError::InputTooShort { .. })
+    ));
 }
 
 /// Test: Trailing null bytes after terminator (should be ignored).
@@ -486,10 +513,10 @@ fn t42_decompress_short_tag_byte() {
     let data = header.to_le_bytes().to_vec();
     // Missing tag byte
     let mut out = Vec::new();
-    assert_eq!(
+    assert!(matches!(
         decompress(&data, &mut out),
-        Err(DecompressionError::InputTooShort)
-    );
+        Err(DecompressionError::InputTooShort { .. })
+    ));
 }
 
 /// Test: Invalid flags in header (e.g. 0xC000).
@@ -570,3 +597,2197 @@ fn t50_final_mixed_corpus() {
     input.extend((0..100).map(|i| i as u8)); // Non-compressible
     assert_round_trip(&input);
 }
+
+// --- Output Limits (Tests 51-54) ---
+
+/// Test: A limit equal to the decompressed size is accepted.
+#[test]
+fn t51_limit_exact_size() {
+    let input = vec![b'A'; 10000];
+    let compressed = compress_to_vec(&input);
+    let options = DecompressOptions::new().max_output(input.len());
+
+    let mut out = Vec::new();
+    decompress_with(&compressed, &mut out, &options).unwrap();
+    assert_eq!(out, input);
+}
+
+/// Test: A limit one byte below the decompressed size is rejected.
+#[test]
+fn t52_limit_one_below() {
+    let input: Vec<u8> = (0..5000).map(|i| (i % 251) as u8).collect();
+    let compressed = compress_to_vec(&input);
+    let options = DecompressOptions::new().max_output(input.len() - 1);
+
+    let mut out = Vec::new();
+    assert!(matches!(
+        decompress_with(&compressed, &mut out, &options),
+        Err(DecompressionError::OutputLimitExceeded { .. })
+    ));
+    assert!(out.len() < input.len());
+}
+
+/// Test: A highly expanding stream stops before the output grows past the limit.
+#[test]
+fn t53_limit_decompression_bomb() {
+    let compressed = compress_to_vec(&vec![0u8; 1024 * 1024]);
+    let options = DecompressOptions::new().max_output(1000);
+
+    let mut out = Vec::new();
+    assert!(matches!(
+        decompress_with(&compressed, &mut out, &options),
+        Err(DecompressionError::OutputLimitExceeded { .. })
+    ));
+    assert!(out.len() <= 1000);
+}
+
+/// Test: The limit only counts bytes appended by the call, not existing content.
+#[test]
+fn t54_limit_ignores_existing_output() {
+    let compressed = compress_to_vec(b"hello");
+    let options = DecompressOptions::new().max_output(5);
+
+    let mut out = vec![0u8; 100];
+    decompress_with(&compressed, &mut out, &options).unwrap();
+    assert_eq!(&out[100..], b"hello");
+}
+
+// --- Fixed Buffer Decompression (Tests 55-58) ---
+
+/// Test: Decompressing into an exactly sized buffer reports the produced length.
+#[test]
+fn t55_into_exact_buffer() {
+    let mut input = Vec::new();
+    for _ in 0..200 {
+        input.extend_from_slice(b"The quick brown fox jumps over the lazy dog. ");
+    }
+    let compressed = compress_to_vec(&input);
+
+    let mut buf = vec![0u8; input.len()];
+    assert_eq!(decompress_into(&compressed, &mut buf), Ok(input.len()));
+    assert_eq!(buf, input);
+}
+
+/// Test: A larger buffer only has its prefix written.
+#[test]
+fn t56_into_larger_buffer() {
+    let input = vec![b'A'; 5000];
+    let compressed = compress_to_vec(&input);
+
+    let mut buf = vec![0xEEu8; 6000];
+    assert_eq!(decompress_into(&compressed, &mut buf), Ok(5000));
+    assert_eq!(&buf[..5000], &input[..]);
+    assert!(buf[5000..].iter().all(|&b| b == 0xEE));
+}
+
+/// Test: A buffer that is one byte short is reported as too small.
+#[test]
+fn t57_into_buffer_too_small() {
+    let input: Vec<u8> = (0..3000).map(|i| ((i * 7) % 13) as u8).collect();
+    let compressed = compress_to_vec(&input);
+
+    let mut buf = vec![0u8; input.len() - 1];
+    assert!(matches!(
+        decompress_into(&compressed, &mut buf),
+        Err(DecompressionError::BufferTooSmall { .. })
+    ));
+}
+
+/// Test: Fixed buffer decompression matches the vector decompressor.
+#[test]
+fn t58_into_matches_vec_output() {
+    let mut input = Vec::new();
+    input.extend(vec![0u8; 5000]);
+    input.extend((0..3000).map(|i| ((i * 37) ^ (i >> 3)) as u8));
+    input.extend(b"abcabcabcabcabc".repeat(300));
+    let compressed = compress_to_vec(&input);
+
+    let mut expected = Vec::new();
+    decompress(&compressed, &mut expected).unwrap();
+
+    let mut buf = vec![0u8; input.len()];
+    let written = decompress_into(&compressed, &mut buf).unwrap();
+    assert_eq!(&buf[..written], &expected[..]);
+}
+
+// --- Streaming Reader (Tests 59-62, 149, `std` feature) ---
+
+/// Test: The streaming decoder reproduces multi-chunk input through small reads.
+#[cfg(feature = "std")]
+#[test]
+fn t59_decoder_small_reads() {
+    use std::io::Read;
+
+    let mut input = Vec::new();
+    input.extend(vec![0u8; 5000]);
+    input.extend((0..6000).map(|i| ((i * 37) ^ (i >> 3)) as u8));
+    let compressed = compress_to_vec(&input);
+
+    let mut decoder = lznt1::Lznt1Decoder::new(compressed.as_slice());
+    let mut out = Vec::new();
+    let mut buf = [0u8; 7];
+    loop {
+        let n = decoder.read(&mut buf).unwrap();
+        if n == 0 {
+            break;
+        }
+        out.extend_from_slice(&buf[..n]);
+    }
+    assert_eq!(out, input);
+}
+
+/// Test: The decoder stops at the end-of-stream marker without consuming trailing data.
+#[cfg(feature = "std")]
+#[test]
+fn t60_decoder_stops_at_terminator() {
+    use std::io::Read;
+
+    let mut data = compress_to_vec(b"hello hello hello");
+    data.extend_from_slice(&[0x00, 0x00]);
+    data.extend_from_slice(b"trailer");
+
+    let mut decoder = lznt1::Lznt1Decoder::new(data.as_slice());
+    let mut out = Vec::new();
+    decoder.read_to_end(&mut out).unwrap();
+    assert_eq!(out, b"hello hello hello");
+    assert_eq!(decoder.into_inner(), b"trailer");
+}
+
+/// Test: A truncated chunk body surfaces as `InvalidData`.
+#[cfg(feature = "std")]
+#[test]
+fn t61_decoder_truncated_body() {
+    use std::io::{ErrorKind, Read};
+
+    let header = HEADER_UNCOMPRESSED | 5; // Size 6
+    let mut data = header.to_le_bytes().to_vec();
+    data.extend_from_slice(b"abc");
+
+    let mut decoder = lznt1::Lznt1Decoder::new(data.as_slice());
+    let err = decoder.read_to_end(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        err.into_inner()
+            .unwrap()
+            .downcast_ref::<DecompressionError>(),
+        Some(&DecompressionError::InputTooShort {
+            position: ErrorPosition::default()
+        })
+    );
+}
+
+/// Test: A chunk expanding beyond 4096 bytes is rejected by the streaming decoder.
+#[cfg(feature = "std")]
+#[test]
+fn t62_decoder_chunk_overflow() {
+    use std::io::Read;
+
+    let data = oversized_chunk();
+    let mut decoder = lznt1::Lznt1Decoder::new(data.as_slice());
+    let err = decoder.read_to_end(&mut Vec::new()).unwrap_err();
+    assert_eq!(
+        err.into_inner()
+            .unwrap()
+            .downcast_ref::<DecompressionError>(),
+        Some(&DecompressionError::ChunkOverflow {
+            position: ErrorPosition {
+                input_offset: 4, // The tuple after header, tag and literal
+                chunk_index: 0,
+                chunk_output_offset: 0,
+            }
+        })
+    );
+}
+
+/// Test: After a failed read, the decoder keeps failing instead of skipping ahead.
+#[cfg(feature = "std")]
+#[test]
+fn t149_decoder_errors_persist() {
+    use std::io::{ErrorKind, Read};
+
+    fn inner_error(err: std::io::Error) -> DecompressionError {
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        err.into_inner()
+            .unwrap()
+            .downcast_ref::<DecompressionError>()
+            .unwrap()
+            .clone()
+    }
+
+    // A bad back-reference in the first chunk, followed by a valid chunk.
+    let mut data = stream_with_bad_second_chunk()[5..].to_vec();
+    data.extend(compress_to_vec(b"next chunk"));
+    let mut decoder = lznt1::Lznt1Decoder::new(data.as_slice());
+    let mut buf = [0u8; 64];
+    let first = inner_error(decoder.read(&mut buf).unwrap_err());
+    assert!(matches!(first, DecompressionError::InvalidOffset { .. }));
+    assert_eq!(inner_error(decoder.read(&mut buf).unwrap_err()), first);
+    assert_eq!(inner_error(decoder.read(&mut buf).unwrap_err()), first);
+
+    // A truncated body must not look like a clean end of stream afterwards.
+    let header = HEADER_UNCOMPRESSED | 5; // Size 6
+    let mut data = header.to_le_bytes().to_vec();
+    data.extend_from_slice(b"abc");
+    let mut decoder = lznt1::Lznt1Decoder::new(data.as_slice());
+    let first = inner_error(decoder.read(&mut buf).unwrap_err());
+    assert_eq!(inner_error(decoder.read(&mut buf).unwrap_err()), first);
+}
+
+// --- Push-Based Streaming (Tests 63-67, 151) ---
+
+/// Test: Feeding one byte at a time resumes mid-header, mid-tag-group and mid-tuple.
+#[test]
+fn t63_stream_byte_by_byte() {
+    let mut input = Vec::new();
+    input.extend(vec![0u8; 5000]);
+    input.extend(b"The quick brown fox jumps over the lazy dog. ".repeat(100));
+    input.extend((0..3000).map(|i| ((i * 37) ^ (i >> 3)) as u8));
+    let compressed = compress_to_vec(&input);
+
+    assert_eq!(stream_decompress(&compressed, 1), Ok(input));
+}
+
+/// Test: Various packet sizes produce identical output.
+#[test]
+fn t64_stream_packet_sizes() {
+    let input = b"abcdefabcdefXYZabcdef".repeat(700);
+    let compressed = compress_to_vec(&input);
+
+    for packet in [2, 3, 5, 16, 255, 4097, compressed.len()] {
+        assert_eq!(stream_decompress(&compressed, packet).as_ref(), Ok(&input));
+    }
+}
+
+/// Test: `finish` reports streams truncated inside a header or chunk body.
+#[test]
+fn t65_stream_finish_truncated() {
+    let compressed = compress_to_vec(&[b'A'; 100]);
+
+    let mut decoder = StreamingDecompressor::new();
+    decoder.feed(&compressed[..1], |_| {}).unwrap();
+    assert!(matches!(
+        decoder.finish(),
+        Err(DecompressionError::UnexpectedEof { .. })
+    ));
+
+    decoder
+        .feed(&compressed[..compressed.len() - 1], |_| {})
+        .unwrap();
+    assert!(matches!(
+        decoder.finish(),
+        Err(DecompressionError::InputTooShort { .. })
+    ));
+}
+
+/// Test: Input after the end-of-stream marker is left unconsumed.
+#[test]
+fn t66_stream_stops_at_terminator() {
+    let mut data = compress_to_vec(b"hello");
+    let stream_len = data.len() + 2;
+    data.extend_from_slice(&[0x00, 0x00, 0xFF, 0xFF]);
+
+    let mut decoder = StreamingDecompressor::new();
+    let mut out = Vec::new();
+    let progress = decoder
+        .feed(&data, |bytes| out.extend_from_slice(bytes))
+        .unwrap();
+
+    assert!(progress.finished);
+    assert_eq!(progress.consumed, stream_len);
+    assert_eq!(progress.produced, 5);
+    assert_eq!(out, b"hello");
+}
+
+/// Test: Invalid offsets are reported, matching `decompress`.
+#[test]
+fn t67_stream_invalid_offset() {
+    let mut data = Vec::new();
+    let header = HEADER_COMPRESSED | 2; // Size 3
+    data.extend_from_slice(&header.to_le_bytes());
+    data.push(0x01); // Tag: 1st item is Ref
+    data.extend_from_slice(&0x0000u16.to_le_bytes());
+
+    assert!(matches!(
+        stream_decompress(&data, 1),
+        Err(DecompressionError::InvalidOffset { .. })
+    ));
+}
+
+/// Test: After an error, `feed` and `finish` keep failing until `reset`.
+#[test]
+fn t151_stream_errors_persist() {
+    let mut data = Vec::new();
+    let header = HEADER_COMPRESSED | 4; // Size 5
+    data.extend_from_slice(&header.to_le_bytes());
+    data.push(0b10); // Tag: Literal, then Ref
+    data.push(b'A');
+    // Offset 16 with a single byte of history.
+    data.extend_from_slice(&0xF000u16.to_le_bytes());
+
+    let mut decoder = StreamingDecompressor::new();
+    let mut out = Vec::new();
+    let err = decoder
+        .feed(&data, |bytes| out.extend_from_slice(bytes))
+        .unwrap_err();
+    assert!(matches!(err, DecompressionError::InvalidOffset { .. }));
+
+    // The next packet must not be read as the rest of the failed tuple.
+    let next = decoder.feed(&[0x00, 0x41, 0x42], |bytes| out.extend_from_slice(bytes));
+    assert_eq!(next, Err(err.clone()));
+    assert_eq!(decoder.finish(), Err(err));
+    assert_eq!(out, b"A");
+
+    decoder.reset();
+    let compressed = compress_to_vec(b"fresh start");
+    let mut fresh = Vec::new();
+    decoder
+        .feed(&compressed, |bytes| fresh.extend_from_slice(bytes))
+        .unwrap();
+    decoder.finish().unwrap();
+    assert_eq!(fresh, b"fresh start");
+}
+
+// --- Strict Chunk-Local Validation (Tests 68-71) ---
+
+/// Test: Back-references into a previous chunk are rejected by default.
+#[test]
+fn t68_strict_rejects_cross_chunk_reference() {
+    let mut data = Vec::new();
+    let raw = HEADER_UNCOMPRESSED | 2; // Size 3
+    data.extend_from_slice(&raw.to_le_bytes());
+    data.extend_from_slice(b"abc");
+    let header = HEADER_COMPRESSED | 2; // Size 3
+    data.extend_from_slice(&header.to_le_bytes());
+    data.push(0x01); // Tag: Ref
+    // Offset 3, Length 3: reaches back into the raw chunk.
+    data.extend_from_slice(&0x2000u16.to_le_bytes());
+
+    let mut out = Vec::new();
+    assert!(matches!(
+        decompress(&data, &mut out),
+        Err(DecompressionError::InvalidOffset { .. })
+    ));
+
+    let mut out = Vec::new();
+    let lenient = DecompressOptions::new().strict(false);
+    decompress_with(&data, &mut out, &lenient).unwrap();
+    assert_eq!(out, b"abcabc");
+}
+
+/// Test: Bytes already in the output vector are not usable as history by default.
+#[test]
+fn t69_strict_ignores_existing_output() {
+    let mut data = Vec::new();
+    let header = HEADER_COMPRESSED | 2; // Size 3
+    data.extend_from_slice(&header.to_le_bytes());
+    data.push(0x01); // Tag: Ref
+    data.extend_from_slice(&0x0000u16.to_le_bytes()); // Offset 1, Length 3
+
+    let mut out = vec![b'Z'];
+    assert!(matches!(
+        decompress(&data, &mut out),
+        Err(DecompressionError::InvalidOffset { .. })
+    ));
+
+    let mut out = vec![b'Z'];
+    let lenient = DecompressOptions::new().strict(false);
+    decompress_with(&data, &mut out, &lenient).unwrap();
+    assert_eq!(out, b"ZZZZ");
+}
+
+/// Test: A chunk expanding beyond 4096 bytes is rejected by default.
+#[test]
+fn t70_strict_chunk_overflow() {
+    let data = oversized_chunk();
+
+    let mut out = Vec::new();
+    assert!(matches!(
+        decompress(&data, &mut out),
+        Err(DecompressionError::ChunkOverflow { .. })
+    ));
+
+    let mut out = Vec::new();
+    let lenient = DecompressOptions::new().strict(false);
+    decompress_with(&data, &mut out, &lenient).unwrap();
+    assert_eq!(out.len(), 4099);
+}
+
+/// Test: A chunk expanding to exactly 4096 bytes is accepted.
+#[test]
+fn t71_strict_full_chunk_accepted() {
+    let input = vec![b'Q'; 4096];
+    let compressed = compress_to_vec(&input);
+    let (is_compressed, _) = parse_header(&compressed);
+    assert!(is_compressed);
+    assert_round_trip(&input);
+}
+
+// --- Header Validation (Tests 72-75) ---
+
+/// Test: Headers with a wrong signature nibble are rejected at `Signature` level.
+#[test]
+fn t72_validation_signature() {
+    let options = DecompressOptions::new().validation(ValidationLevel::Signature);
+
+    for header in [0x0003u16, 0xF003, 0x4003, 0xC003] {
+        let mut data = header.to_le_bytes().to_vec();
+        data.extend_from_slice(&[0x00, b'a', b'b', b'c']);
+
+        let mut out = Vec::new();
+        assert!(
+            matches!(
+                decompress_with(&data, &mut out, &options),
+                Err(DecompressionError::InvalidSignature { .. })
+            ),
+            "header {header:#06x}"
+        );
+    }
+
+    // The default lenient level keeps decoding such headers.
+    let mut data = 0x0003u16.to_le_bytes().to_vec();
+    data.extend_from_slice(b"abcd");
+    let mut out = Vec::new();
+    decompress(&data, &mut out).unwrap();
+    assert_eq!(out, b"abcd");
+}
+
+/// Test: Short raw chunks are only accepted at the end of the stream at `Strict` level.
+#[test]
+fn t73_validation_short_raw_chunk() {
+    let options = DecompressOptions::new().validation(ValidationLevel::Strict);
+    let raw = HEADER_UNCOMPRESSED | 2; // Size 3
+
+    let mut data = raw.to_le_bytes().to_vec();
+    data.extend_from_slice(b"abc");
+    let mut out = Vec::new();
+    decompress_with(&data, &mut out, &options).unwrap();
+
+    // A terminator may follow the short final chunk.
+    data.extend_from_slice(&[0x00, 0x00]);
+    let mut out = Vec::new();
+    decompress_with(&data, &mut out, &options).unwrap();
+
+    // Another chunk may not.
+    data.truncate(5);
+    data.extend_from_slice(&raw.to_le_bytes());
+    data.extend_from_slice(b"def");
+    let mut out = Vec::new();
+    assert!(matches!(
+        decompress_with(&data, &mut out, &options),
+        Err(DecompressionError::ShortRawChunk { .. })
+    ));
+}
+
+/// Test: Compressed chunks larger than their decompressed data are rejected at `Strict` level.
+#[test]
+fn t74_validation_compressed_chunk_too_large() {
+    let header = HEADER_COMPRESSED | 3; // Size 4
+    let mut data = header.to_le_bytes().to_vec();
+    data.extend_from_slice(&[0x00, b'a', b'b', b'c']); // 3 literals in 4 bytes
+
+    let options = DecompressOptions::new().validation(ValidationLevel::Strict);
+    let mut out = Vec::new();
+    assert!(matches!(
+        decompress_with(&data, &mut out, &options),
+        Err(DecompressionError::CompressedChunkTooLarge { .. })
+    ));
+
+    let mut out = Vec::new();
+    decompress(&data, &mut out).unwrap();
+    assert_eq!(out, b"abc");
+}
+
+/// Test: Streams produced by the compressor pass `Strict` validation.
+#[test]
+fn t75_validation_accepts_compressor_output() {
+    let mut input = Vec::new();
+    input.extend((0..5000).map(|i| ((i * 37) ^ (i >> 3)) as u8));
+    input.extend(vec![0u8; 6000]);
+    input.extend(b"tail");
+    let compressed = compress_to_vec(&input);
+
+    let options = DecompressOptions::new().validation(ValidationLevel::Strict);
+    let mut out = Vec::new();
+    decompress_with(&compressed, &mut out, &options).unwrap();
+    assert_eq!(out, input);
+}
+
+// --- Error Positions (Tests 76-79) ---
+
+/// Builds a raw chunk `abc` followed by a compressed chunk whose tuple
+/// (at input offset 9) reaches 5 bytes back with only 1 byte of chunk history.
+fn stream_with_bad_second_chunk() -> Vec<u8> {
+    let mut data = Vec::new();
+    let raw = HEADER_UNCOMPRESSED | 2; // Size 3
+    data.extend_from_slice(&raw.to_le_bytes());
+    data.extend_from_slice(b"abc");
+    let header = HEADER_COMPRESSED | 3; // Size 4
+    data.extend_from_slice(&header.to_le_bytes());
+    data.push(0b10); // Tag: Literal, then Ref
+    data.push(b'x');
+    // Offset 5, Length 3. The split is still 12 bits after one byte of output.
+    data.extend_from_slice(&0x4000u16.to_le_bytes());
+    data
+}
+
+/// Test: `InvalidOffset` reports the tuple location, requested offset and history.
+#[test]
+fn t76_error_position_invalid_offset() {
+    let data = stream_with_bad_second_chunk();
+    let mut out = Vec::new();
+    assert_eq!(
+        decompress(&data, &mut out),
+        Err(DecompressionError::InvalidOffset {
+            position: ErrorPosition {
+                input_offset: 9,
+                chunk_index: 1,
+                chunk_output_offset: 3,
+            },
+            offset: 5,
+            available: 1,
+        })
+    );
+}
+
+/// Test: Truncated chunks are reported at their header.
+#[test]
+fn t77_error_position_truncated_chunk() {
+    let mut data = compress_to_vec(&vec![b'A'; 8192]);
+    let header_offset = data.len();
+    let header = HEADER_UNCOMPRESSED | 9; // Size 10
+    data.extend_from_slice(&header.to_le_bytes());
+    data.extend_from_slice(b"abc");
+
+    let mut out = Vec::new();
+    let err = decompress(&data, &mut out).unwrap_err();
+    assert!(matches!(err, DecompressionError::InputTooShort { .. }));
+    assert_eq!(
+        *err.position(),
+        ErrorPosition {
+            input_offset: header_offset,
+            chunk_index: 2,
+            chunk_output_offset: 8192,
+        }
+    );
+}
+
+/// Test: The push-based decompressor reports the same position as `decompress`.
+#[test]
+fn t78_error_position_streaming() {
+    let data = stream_with_bad_second_chunk();
+    let mut out = Vec::new();
+    let expected = decompress(&data, &mut out).unwrap_err();
+
+    assert_eq!(stream_decompress(&data, 1), Err(expected.clone()));
+    assert_eq!(stream_decompress(&data, data.len()), Err(expected));
+}
+
+/// Test: The error message includes the position.
+#[test]
+fn t79_error_position_display() {
+    let data = stream_with_bad_second_chunk();
+    let mut out = Vec::new();
+    let message = decompress(&data, &mut out).unwrap_err().to_string();
+    assert_eq!(
+        message,
+        "Lookback offset 5 out of bounds (1 bytes of history) at input offset 9 \
+         (chunk 1, chunk output offset 3)"
+    );
+}
+
+// --- Fragment Decompression (Tests 80-83) ---
+
+/// Test: Fragments spanning chunk boundaries match the full decompression.
+#[test]
+fn t80_fragment_matches_full_output() {
+    let mut input = Vec::new();
+    input.extend(b"The quick brown fox jumps over the lazy dog. ".repeat(200));
+    input.extend((0..5000).map(|i| ((i * 37) ^ (i >> 3)) as u8));
+    let compressed = compress_to_vec(&input);
+
+    for (offset, len) in [
+        (0, 10),
+        (4090, 20),
+        (5000, 6000),
+        (8192, 4096),
+        (13000, 100),
+    ] {
+        let mut buf = vec![0u8; len];
+        let written = decompress_fragment(&compressed, offset, &mut buf).unwrap();
+        let expected = &input[offset..(offset + len).min(input.len())];
+        assert_eq!(&buf[..written], expected, "offset {offset}, len {len}");
+    }
+}
+
+/// Test: Fragments starting past the end of the data are empty.
+#[test]
+fn t81_fragment_past_end() {
+    let compressed = compress_to_vec(&vec![b'A'; 5000]);
+    let mut buf = [0u8; 16];
+    assert_eq!(decompress_fragment(&compressed, 5000, &mut buf), Ok(0));
+    assert_eq!(decompress_fragment(&compressed, 100_000, &mut buf), Ok(0));
+    assert_eq!(decompress_fragment(&compressed, 4990, &mut buf), Ok(10));
+}
+
+/// Test: Chunks before the requested range are skipped without being decoded.
+#[test]
+fn t82_fragment_skips_preceding_chunks() {
+    let mut data = Vec::new();
+    let header = HEADER_COMPRESSED | 2; // Size 3
+    data.extend_from_slice(&header.to_le_bytes());
+    data.push(0x01); // Tag: Ref with no history (corrupt)
+    data.extend_from_slice(&0x0000u16.to_le_bytes());
+    data.extend(compress_to_vec(b"second chunk"));
+
+    let mut buf = [0u8; 12];
+    assert_eq!(decompress_fragment(&data, 4096, &mut buf), Ok(12));
+    assert_eq!(&buf, b"second chunk");
+
+    assert!(matches!(
+        decompress_fragment(&data, 0, &mut buf),
+        Err(DecompressionError::InvalidOffset { .. })
+    ));
+}
+
+/// Test: Short non-final chunks are padded with zeros to 4096 bytes.
+#[test]
+fn t83_fragment_pads_short_chunks() {
+    let raw = HEADER_UNCOMPRESSED | 2; // Size 3
+    let mut data = raw.to_le_bytes().to_vec();
+    data.extend_from_slice(b"abc");
+    data.extend_from_slice(&raw.to_le_bytes());
+    data.extend_from_slice(b"def");
+
+    let mut buf = vec![0xEEu8; 5000];
+    assert_eq!(decompress_fragment(&data, 0, &mut buf), Ok(4099));
+    assert_eq!(&buf[..3], b"abc");
+    assert!(buf[3..4096].iter().all(|&b| b == 0));
+    assert_eq!(&buf[4096..4099], b"def");
+}
+
+// --- Chunk Iterator (Tests 84-87) ---
+
+/// Test: The iterator reports each chunk's header, offset and index.
+#[test]
+fn t84_chunks_offsets_and_indices() {
+    let input: Vec<u8> = (0..10000).map(|i| (i % 251) as u8).collect();
+    let compressed = compress_to_vec(&input);
+
+    let mut expected_offset = 0;
+    let mut output = Vec::new();
+    for (i, chunk) in chunks(&compressed).enumerate() {
+        let chunk = chunk.unwrap();
+        assert_eq!(chunk.index, i);
+        assert_eq!(chunk.offset, expected_offset);
+        assert!(chunk.header.has_valid_signature());
+        assert_eq!(chunk.body.len(), chunk.header.body_len);
+        expected_offset += ChunkHeader::SIZE + chunk.header.body_len;
+
+        let mut buf = [0u8; 4096];
+        let n = chunk.decompress_into(&mut buf).unwrap();
+        output.extend_from_slice(&buf[..n]);
+    }
+    assert_eq!(expected_offset, compressed.len());
+    assert_eq!(output, input);
+}
+
+/// Test: Iteration stops at end-of-stream markers and trailing null bytes.
+#[test]
+fn t85_chunks_terminators() {
+    let mut data = compress_to_vec(b"first");
+    let first_len = data.len();
+    data.extend_from_slice(&0x0000u16.to_le_bytes());
+    data.extend_from_slice(b"trailing garbage");
+
+    let mut iter = chunks(&data);
+    assert!(iter.next().unwrap().is_ok());
+    assert!(!iter.has_next_chunk());
+    assert!(iter.next().is_none());
+    assert_eq!(iter.offset(), first_len + 2);
+
+    let mut data = compress_to_vec(b"first");
+    data.push(0x00);
+    assert_eq!(chunks(&data).count(), 1);
+    assert_eq!(chunks(&[]).count(), 0);
+}
+
+/// Test: A truncated chunk yields a single error, after which the iterator is fused.
+#[test]
+fn t86_chunks_truncated_then_fused() {
+    let mut data = compress_to_vec(b"first chunk");
+    let second_offset = data.len();
+    let header = HEADER_UNCOMPRESSED | 9; // Size 10
+    data.extend_from_slice(&header.to_le_bytes());
+    data.extend_from_slice(b"abc");
+
+    let mut iter = chunks(&data);
+    assert!(iter.next().unwrap().is_ok());
+    assert_eq!(
+        iter.next(),
+        Some(Err(DecompressionError::InputTooShort {
+            position: ErrorPosition {
+                input_offset: second_offset,
+                chunk_index: 1,
+                chunk_output_offset: 0,
+            }
+        }))
+    );
+    assert!(iter.next().is_none());
+    assert!(iter.next().is_none());
+}
+
+/// Test: Header parsing exposes the flag, signature and body length.
+#[test]
+fn t87_chunk_header_parse() {
+    assert_eq!(ChunkHeader::parse(0x0000), None);
+    assert_eq!(
+        ChunkHeader::parse(0xB00C),
+        Some(ChunkHeader {
+            compressed: true,
+            signature: 0b011,
+            body_len: 13,
+        })
+    );
+
+    let raw = ChunkHeader::parse(0x3FFF).unwrap();
+    assert!(!raw.compressed);
+    assert_eq!(raw.body_len, 4096);
+    assert!(raw.has_valid_signature());
+
+    let bad = ChunkHeader::parse(0xC000).unwrap();
+    assert_eq!(bad.signature, 0b100);
+    assert!(!bad.has_valid_signature());
+}
+
+// --- Zero Padding (Tests 88-91) ---
+
+/// Builds two short compressed chunks holding `"abc"` and `"def"`.
+fn two_short_chunks() -> Vec<u8> {
+    let mut data = compress_to_vec(b"abc");
+    data.extend(compress_to_vec(b"def"));
+    data
+}
+
+/// Test: Short chunks are concatenated unless padding is enabled.
+#[test]
+fn t88_zero_pad_disabled_by_default() {
+    let mut output = Vec::new();
+    decompress(&two_short_chunks(), &mut output).unwrap();
+    assert_eq!(output, b"abcdef");
+}
+
+/// Test: Short non-final chunks are padded so the next chunk starts at 4096.
+#[test]
+fn t89_zero_pad_short_chunks() {
+    let options = DecompressOptions::new().zero_pad(true);
+    let mut output = Vec::new();
+    decompress_with(&two_short_chunks(), &mut output, &options).unwrap();
+
+    assert_eq!(output.len(), 4099);
+    assert_eq!(&output[..3], b"abc");
+    assert!(output[3..4096].iter().all(|&b| b == 0));
+    assert_eq!(&output[4096..], b"def");
+
+    let mut fragment = vec![0u8; 5000];
+    let written = decompress_fragment(&two_short_chunks(), 0, &mut fragment).unwrap();
+    assert_eq!(&fragment[..written], output.as_slice());
+}
+
+/// Test: The final chunk is never padded, even before an end-of-stream marker.
+#[test]
+fn t90_zero_pad_final_chunk() {
+    let options = DecompressOptions::new().zero_pad(true);
+    let mut data = compress_to_vec(b"only chunk");
+    data.extend_from_slice(&0x0000u16.to_le_bytes());
+    data.extend(compress_to_vec(b"ignored"));
+
+    let mut output = Vec::new();
+    decompress_with(&data, &mut output, &options).unwrap();
+    assert_eq!(output, b"only chunk");
+
+    let input: Vec<u8> = (0..4096 * 3).map(|i| (i % 13) as u8).collect();
+    let mut output = Vec::new();
+    decompress_with(&compress_to_vec(&input), &mut output, &options).unwrap();
+    assert_eq!(output, input);
+}
+
+/// Test: Padding counts towards the output limit.
+#[test]
+fn t91_zero_pad_respects_limit() {
+    let options = DecompressOptions::new().zero_pad(true).max_output(4098);
+    let mut output = Vec::new();
+    assert!(matches!(
+        decompress_with(&two_short_chunks(), &mut output, &options),
+        Err(DecompressionError::OutputLimitExceeded { .. })
+    ));
+}
+
+// --- Parallel Decompression (Tests 92-95, 153) ---
+
+/// Test: Parallel decompression matches sequential decompression.
+#[cfg(feature = "rayon")]
+#[test]
+fn t92_par_decompress_matches_sequential() {
+    let mut input: Vec<u8> = (0..50_000).map(|i| (i % 251) as u8).collect();
+    input.extend(vec![b'Z'; 20_000]);
+    input.extend(b"tail".repeat(1000));
+    let compressed = compress_to_vec(&input);
+
+    let mut output = b"prefix".to_vec();
+    lznt1::par_decompress(&compressed, &mut output).unwrap();
+    assert_eq!(&output[..6], b"prefix");
+    assert_eq!(&output[6..], input.as_slice());
+}
+
+/// Test: Empty streams and streams ending with markers are handled like `decompress`.
+#[cfg(feature = "rayon")]
+#[test]
+fn t93_par_decompress_terminators() {
+    let mut output = Vec::new();
+    lznt1::par_decompress(&[], &mut output).unwrap();
+    assert!(output.is_empty());
+
+    let mut data = two_short_chunks();
+    data.extend_from_slice(&0x0000u16.to_le_bytes());
+    data.extend_from_slice(b"garbage");
+    lznt1::par_decompress(&data, &mut output).unwrap();
+    assert_eq!(output, b"abcdef");
+}
+
+/// Test: The first error in stream order is reported, with the same position.
+#[cfg(feature = "rayon")]
+#[test]
+fn t94_par_decompress_first_error() {
+    let data = stream_with_bad_second_chunk();
+    let mut sequential = Vec::new();
+    let expected = decompress(&data, &mut sequential).unwrap_err();
+
+    let mut output = b"keep".to_vec();
+    assert_eq!(lznt1::par_decompress(&data, &mut output), Err(expected));
+    assert_eq!(output, b"keep");
+}
+
+/// Test: Header errors are only reported after every preceding chunk is validated.
+#[cfg(feature = "rayon")]
+#[test]
+fn t95_par_decompress_truncated_header() {
+    let mut data = compress_to_vec(&vec![b'x'; 5000]);
+    data.push(0x12);
+
+    let mut sequential = Vec::new();
+    let expected = decompress(&data, &mut sequential).unwrap_err();
+    assert!(matches!(expected, DecompressionError::UnexpectedEof { .. }));
+
+    let mut output = Vec::new();
+    assert_eq!(lznt1::par_decompress(&data, &mut output), Err(expected));
+    assert!(output.is_empty());
+}
+
+/// Test: Short chunks in the middle of a stream are packed like `decompress` does.
+#[cfg(feature = "rayon")]
+#[test]
+fn t153_par_decompress_short_middle_chunks() {
+    let full = text_like_input(4096);
+    let mut data = compress_to_vec(&full);
+    data.extend(two_short_chunks());
+    data.extend(compress_to_vec(&vec![b'Q'; 9000]));
+    data.extend(two_short_chunks());
+
+    let mut expected = Vec::new();
+    decompress(&data, &mut expected).unwrap();
+    assert_eq!(expected.len(), 4096 + 6 + 9000 + 6);
+
+    let mut output = b"prefix".to_vec();
+    lznt1::par_decompress(&data, &mut output).unwrap();
+    assert_eq!(&output[..6], b"prefix");
+    assert_eq!(&output[6..], expected.as_slice());
+
+    // An error after the short chunks reports the packed output offset.
+    let mut bad = data.clone();
+    bad.extend(stream_with_bad_second_chunk());
+    let mut sequential = Vec::new();
+    let err = decompress(&bad, &mut sequential).unwrap_err();
+    let mut output = b"keep".to_vec();
+    assert_eq!(lznt1::par_decompress(&bad, &mut output), Err(err));
+    assert_eq!(output, b"keep");
+}
+
+// --- Decompression Status (Tests 96-99) ---
+
+/// Test: The status reports where an embedded stream ends.
+#[test]
+fn t96_status_embedded_stream() {
+    let mut record = compress_to_vec(b"embedded payload");
+    let stream_len = record.len() + 2;
+    record.extend_from_slice(&0x0000u16.to_le_bytes());
+    record.extend_from_slice(&[0xFF; 10]);
+
+    let mut output = Vec::new();
+    let status = decompress_with_status(&record, &mut output, &DecompressOptions::new()).unwrap();
+    assert_eq!(
+        status,
+        DecompressStatus {
+            consumed: stream_len,
+            produced: 16,
+            terminated: true,
+        }
+    );
+    assert_eq!(output, b"embedded payload");
+}
+
+/// Test: Streams ending with the input are not reported as terminated.
+#[test]
+fn t97_status_unterminated() {
+    let input = vec![b'q'; 9000];
+    let compressed = compress_to_vec(&input);
+
+    let mut output = b"ab".to_vec();
+    let status =
+        decompress_with_status(&compressed, &mut output, &DecompressOptions::new()).unwrap();
+    assert_eq!(status.consumed, compressed.len());
+    assert_eq!(status.produced, 9000);
+    assert!(!status.terminated);
+
+    let mut nul_terminated = compressed.clone();
+    nul_terminated.push(0x00);
+    let status =
+        decompress_with_status(&nul_terminated, &mut output, &DecompressOptions::new()).unwrap();
+    assert_eq!(status.consumed, nul_terminated.len());
+    assert!(status.terminated);
+}
+
+/// Test: Errors report the output produced before the failure.
+#[test]
+fn t98_status_error_reports_produced() {
+    let mut output = Vec::new();
+    let err = decompress_with_status(
+        &stream_with_bad_second_chunk(),
+        &mut output,
+        &DecompressOptions::new(),
+    )
+    .unwrap_err();
+
+    assert!(matches!(
+        err.error,
+        DecompressionError::InvalidOffset { .. }
+    ));
+    assert_eq!(err.produced, 4);
+    assert_eq!(output, b"abcx");
+}
+
+/// Test: The output limit error also reports the bytes produced.
+#[test]
+fn t99_status_output_limit() {
+    let compressed = compress_to_vec(&vec![0u8; 10000]);
+    let options = DecompressOptions::new().max_output(5000);
+
+    let mut output = Vec::new();
+    let err = decompress_with_status(&compressed, &mut output, &options).unwrap_err();
+    assert!(matches!(
+        err.error,
+        DecompressionError::OutputLimitExceeded { .. }
+    ));
+    assert_eq!(err.produced, output.len());
+    assert!(err.produced <= 5000);
+}
+
+// --- Salvage Mode (Tests 100-103, 148) ---
+
+/// Test: Intact streams are salvaged exactly as they decompress.
+#[test]
+fn t100_salvage_clean_stream() {
+    let input: Vec<u8> = (0..10000).map(|i| (i % 97) as u8).collect();
+    let mut output = Vec::new();
+    let report = decompress_salvage(&compress_to_vec(&input), &mut output, SalvageFill::Zeros);
+    assert!(report.is_clean());
+    assert_eq!(output, input);
+}
+
+/// Test: A chunk with a bad back-reference is replaced by the fill and reported.
+#[test]
+fn t101_salvage_bad_chunk_fill() {
+    let mut data = stream_with_bad_second_chunk();
+    let bad_range = 5..data.len();
+    data.extend(compress_to_vec(b"third"));
+
+    let mut zeros = Vec::new();
+    let report = decompress_salvage(&data, &mut zeros, SalvageFill::Zeros);
+    assert_eq!(report.damaged.len(), 1);
+    let damaged = &report.damaged[0];
+    assert_eq!(damaged.index, 1);
+    assert_eq!(damaged.input_range, bad_range);
+    assert!(matches!(
+        damaged.error,
+        DecompressionError::InvalidOffset { .. }
+    ));
+    assert_eq!(zeros.len(), 3 + 4096 + 5);
+    assert!(zeros[3..4099].iter().all(|&b| b == 0));
+    assert_eq!(&zeros[4099..], b"third");
+
+    let mut partial = Vec::new();
+    decompress_salvage(&data, &mut partial, SalvageFill::Partial);
+    assert_eq!(&partial[..4], b"abcx");
+    assert!(partial[4..4099].iter().all(|&b| b == 0));
+    assert_eq!(&partial[4099..], b"third");
+}
+
+/// Test: Decoding resynchronizes on the next plausible header after garbage.
+#[test]
+fn t102_salvage_resync_after_garbage() {
+    let first = compress_to_vec(b"first chunk");
+    let second = compress_to_vec(b"second chunk");
+    let mut data = first.clone();
+    data.extend_from_slice(&[0xFF, 0xFF, 0x12, 0x34, 0x56]);
+    data.extend_from_slice(&second);
+
+    let mut output = Vec::new();
+    let report = decompress_salvage(&data, &mut output, SalvageFill::Zeros);
+    assert_eq!(report.damaged.len(), 1);
+    assert_eq!(report.damaged[0].input_range, first.len()..first.len() + 5);
+    assert!(matches!(
+        report.damaged[0].error,
+        DecompressionError::InputTooShort { .. }
+    ));
+    assert_eq!(&output[..11], b"first chunk");
+    assert_eq!(&output[11 + 4096..], b"second chunk");
+}
+
+/// Test: A truncated final chunk keeps its decodable prefix with the partial fill.
+#[test]
+fn t103_salvage_truncated_chunk() {
+    let input = b"truncated stream content that is cut short";
+    let mut data = compress_to_vec(input);
+    data.truncate(data.len() - 10);
+
+    let mut output = Vec::new();
+    let report = decompress_salvage(&data, &mut output, SalvageFill::Partial);
+    assert_eq!(report.damaged.len(), 1);
+    assert_eq!(report.damaged[0].input_range, 0..data.len());
+    assert!(matches!(
+        report.damaged[0].error,
+        DecompressionError::InputTooShort { .. }
+    ));
+    assert_eq!(output.len(), 4096);
+    assert!(output.starts_with(&input[..20]));
+}
+
+/// Test: Chunks with bad signature bits are salvaged exactly as they decompress.
+#[test]
+fn t148_salvage_matches_lenient_decompress() {
+    let input = text_like_input(3 * 4096 + 100);
+    let mut data = compress_to_vec(&input);
+    data[1] ^= 0x40; // Signature bits of the first header
+
+    let mut expected = Vec::new();
+    decompress(&data, &mut expected).unwrap();
+    assert_eq!(expected, input);
+
+    for fill in [SalvageFill::Zeros, SalvageFill::Partial] {
+        let mut output = Vec::new();
+        let report = decompress_salvage(&data, &mut output, fill);
+        assert!(report.is_clean());
+        assert_eq!(output, expected);
+    }
+}
+
+// --- Output Sinks (Tests 104-107) ---
+
+/// Test: Decompressing into a slice sink matches `decompress` and reports a full buffer.
+#[test]
+fn t104_decompress_to_slice_sink() {
+    let input: Vec<u8> = (0..9000).map(|i| (i % 31) as u8).collect();
+    let compressed = compress_to_vec(&input);
+
+    let mut buf = vec![0u8; 10000];
+    let mut sink = SliceSink::new(&mut buf);
+    decompress_to_sink(&compressed, &mut sink, &DecompressOptions::new()).unwrap();
+    assert_eq!(sink.written(), input.as_slice());
+
+    let mut small = [0u8; 100];
+    let mut sink = SliceSink::new(&mut small);
+    assert!(matches!(
+        decompress_to_sink(&compressed, &mut sink, &DecompressOptions::new()),
+        Err(DecompressionError::BufferTooSmall { .. })
+    ));
+}
+
+/// Test: Counting sinks measure the output and honor their limit.
+#[test]
+fn t105_counting_sink() {
+    let compressed = compress_to_vec(&vec![b'c'; 12345]);
+
+    let mut counter = CountingSink::new();
+    decompress_to_sink(&compressed, &mut counter, &DecompressOptions::new()).unwrap();
+    assert_eq!(counter.count(), 12345);
+
+    let mut bounded = CountingSink::with_limit(10000);
+    assert!(matches!(
+        decompress_to_sink(&compressed, &mut bounded, &DecompressOptions::new()),
+        Err(DecompressionError::BufferTooSmall { .. })
+    ));
+
+    let mut counter = CountingSink::new();
+    compress_to_sink(&vec![b'c'; 12345], &mut counter, &CompressOptions::new()).unwrap();
+    assert_eq!(counter.count(), compressed.len());
+}
+
+/// Test: Compressing into sinks produces the same bytes as `compress`.
+#[test]
+fn t106_compress_to_sink() {
+    let input: Vec<u8> = (0..10000).map(|i| ((i * 7) % 256) as u8).collect();
+    let expected = compress_to_vec(&input);
+
+    let mut vec_sink = Vec::new();
+    compress_to_sink(&input, &mut vec_sink, &CompressOptions::new()).unwrap();
+    assert_eq!(vec_sink, expected);
+
+    let mut buf = vec![0u8; expected.len()];
+    let mut sink = SliceSink::new(&mut buf);
+    compress_to_sink(&input, &mut sink, &CompressOptions::new()).unwrap();
+    assert_eq!(sink.written(), expected.as_slice());
+
+    // Only complete chunks are written before the sink fills up.
+    let mut short = vec![0u8; expected.len() - 1];
+    let mut sink = SliceSink::new(&mut short);
+    assert_eq!(
+        compress_to_sink(&input, &mut sink, &CompressOptions::new()),
+        Err(SinkFull)
+    );
+    let written = sink.written().len();
+    assert!(written < expected.len());
+    assert_eq!(sink.written(), &expected[..written]);
+}
+
+/// Sink keeping only the last 4096 bytes, and a checksum of everything written.
+struct ChecksumSink {
+    window: Vec<u8>,
+    written: usize,
+    sum: u64,
+}
+
+impl ChecksumSink {
+    fn record(&mut self, byte: u8) {
+        if self.window.len() == 4096 {
+            self.window.remove(0);
+        }
+        self.window.push(byte);
+        self.written += 1;
+        self.sum = self.sum.wrapping_mul(31).wrapping_add(u64::from(byte));
+    }
+}
+
+impl OutputSink for ChecksumSink {
+    fn len(&self) -> usize {
+        self.written
+    }
+
+    fn push(&mut self, byte: u8) -> Result<(), SinkFull> {
+        self.record(byte);
+        Ok(())
+    }
+
+    fn extend_from_slice(&mut self, bytes: &[u8]) -> Result<(), SinkFull> {
+        bytes.iter().for_each(|&b| self.record(b));
+        Ok(())
+    }
+
+    fn copy_within_history(&mut self, offset: usize, length: usize) -> Result<(), SinkFull> {
+        for _ in 0..length {
+            let byte = self.window[self.window.len() - offset];
+            self.record(byte);
+        }
+        Ok(())
+    }
+}
+
+/// Test: Custom sinks retaining only the current chunk can observe the output.
+#[test]
+fn t107_custom_sink() {
+    let input: Vec<u8> = b"checksum me "
+        .iter()
+        .cycle()
+        .take(20000)
+        .copied()
+        .collect();
+    let compressed = compress_to_vec(&input);
+
+    let mut sink = ChecksumSink {
+        window: Vec::new(),
+        written: 0,
+        sum: 0,
+    };
+    decompress_to_sink(&compressed, &mut sink, &DecompressOptions::new()).unwrap();
+
+    let expected = input.iter().fold(0u64, |sum, &b| {
+        sum.wrapping_mul(31).wrapping_add(u64::from(b))
+    });
+    assert_eq!(sink.len(), input.len());
+    assert_eq!(sink.sum, expected);
+}
+
+// --- Compression Levels (Tests 108-111) ---
+
+/// Builds a moderately compressible, text-like input.
+fn text_like_input(len: usize) -> Vec<u8> {
+    let words: [&[u8]; 6] = [
+        b"lorem ", b"ipsum ", b"dolor ", b"sit ", b"amet, ", b"elit. ",
+    ];
+    let mut seed = 0x2545_F491u32;
+    let mut data = Vec::with_capacity(len);
+    while data.len() < len {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        data.extend_from_slice(words[(seed % 6) as usize]);
+    }
+    data.truncate(len);
+    data
+}
+
+/// Test: The default options reproduce `compress` exactly.
+#[test]
+fn t108_default_level_matches_compress() {
+    let input = text_like_input(20000);
+    let mut with_default = Vec::new();
+    compress_with(&input, &mut with_default, &CompressOptions::default());
+    assert_eq!(with_default, compress_to_vec(&input));
+}
+
+/// Test: Every level round-trips.
+#[test]
+fn t109_levels_round_trip() {
+    let inputs = [
+        text_like_input(30000),
+        vec![0u8; 10000],
+        (0..5000).map(|i| (i * 13 % 256) as u8).collect(),
+    ];
+    for options in [
+        CompressOptions::fastest(),
+        CompressOptions::new(),
+        CompressOptions::best(),
+        CompressOptions::new()
+            .max_chain(0)
+            .nice_length(3)
+            .insert_matched(false),
+    ] {
+        for input in &inputs {
+            let mut compressed = Vec::new();
+            compress_with(input, &mut compressed, &options);
+            let mut output = Vec::new();
+            decompress(&compressed, &mut output).unwrap();
+            assert_eq!(&output, input, "{options:?}");
+        }
+    }
+}
+
+/// Test: Higher levels compress at least as well on typical data.
+#[test]
+fn t110_levels_ordered_by_ratio() {
+    let input = text_like_input(40000);
+    let size = |options: &CompressOptions| {
+        let mut compressed = Vec::new();
+        compress_with(&input, &mut compressed, options);
+        compressed.len()
+    };
+    let fastest = size(&CompressOptions::fastest());
+    let default = size(&CompressOptions::new());
+    let best = size(&CompressOptions::best());
+    assert!(best <= default, "best {best} > default {default}");
+    assert!(default <= fastest, "default {default} > fastest {fastest}");
+}
+
+/// Test: The sink entry point honors the options.
+#[test]
+fn t111_compress_to_sink_with_level() {
+    let input = text_like_input(10000);
+    let mut expected = Vec::new();
+    compress_with(&input, &mut expected, &CompressOptions::fastest());
+
+    let mut sink = Vec::new();
+    compress_to_sink(&input, &mut sink, &CompressOptions::fastest()).unwrap();
+    assert_eq!(sink, expected);
+}
+
+// --- Optimal Parse (Tests 112-115) ---
+
+/// Test: The optimal parse round-trips on varied data.
+#[test]
+fn t112_optimal_round_trip() {
+    let mut mixed = text_like_input(9000);
+    mixed.extend((0..3000).map(|i| (i * 97 % 251) as u8));
+    mixed.extend(vec![0u8; 5000]);
+    mixed.extend(b"abcabdabcabe".repeat(400));
+
+    for input in [mixed, vec![b'x'; 4096], b"ab".to_vec(), Vec::new()] {
+        let mut compressed = Vec::new();
+        compress_with(&input, &mut compressed, &CompressOptions::maximum());
+        let mut output = Vec::new();
+        decompress(&compressed, &mut output).unwrap();
+        assert_eq!(output, input);
+    }
+}
+
+/// Test: Every chunk of the optimal parse is at most as large as the greedy one.
+#[test]
+fn t113_optimal_never_larger_than_greedy() {
+    let mut input = text_like_input(30000);
+    input.extend(b"0123456789abcdef".repeat(600));
+    input.extend((0..8000).map(|i| ((i * i) % 256) as u8));
+
+    let greedy = CompressOptions::best();
+    for options in [greedy, greedy.max_chain(4)] {
+        let mut greedy_out = Vec::new();
+        compress_with(&input, &mut greedy_out, &options);
+        let mut optimal_out = Vec::new();
+        compress_with(&input, &mut optimal_out, &options.optimal(true));
+
+        let greedy_chunks: Vec<_> = chunks(&greedy_out).map(Result::unwrap).collect();
+        let optimal_chunks: Vec<_> = chunks(&optimal_out).map(Result::unwrap).collect();
+        assert_eq!(greedy_chunks.len(), optimal_chunks.len());
+        for (g, o) in greedy_chunks.iter().zip(&optimal_chunks) {
+            assert!(o.body.len() <= g.body.len(), "chunk {}", g.index);
+        }
+    }
+}
+
+/// Test: The optimal parse improves on greedy matching where the split narrows.
+#[test]
+fn t114_optimal_beats_greedy() {
+    let input = text_like_input(40000);
+    let mut best = Vec::new();
+    compress_with(&input, &mut best, &CompressOptions::best());
+    let mut maximum = Vec::new();
+    compress_with(&input, &mut maximum, &CompressOptions::maximum());
+    assert!(
+        maximum.len() < best.len(),
+        "maximum {} >= best {}",
+        maximum.len(),
+        best.len()
+    );
+}
+
+/// Test: Incompressible chunks are still stored raw.
+#[test]
+fn t115_optimal_incompressible() {
+    let input: Vec<u8> = (0..200).map(|i| (i * 7) as u8).collect();
+    let mut compressed = Vec::new();
+    compress_with(&input, &mut compressed, &CompressOptions::maximum());
+    assert_eq!(compressed.len(), 202);
+    assert_eq!(parse_header(&compressed), (false, 200));
+}
+
+// --- Lazy Matching (Tests 116-119) ---
+
+/// Test: Lazy matching round-trips at every step count and hash insertion mode.
+#[test]
+fn t116_lazy_roundtrip() {
+    let mut input = text_like_input(20000);
+    input.extend(vec![0u8; 5000]);
+    input.extend(b"abcabdabcabe".repeat(400));
+
+    for base in [CompressOptions::new(), CompressOptions::fastest()] {
+        for steps in 1..=2 {
+            let mut compressed = Vec::new();
+            compress_with(&input, &mut compressed, &base.lazy(steps));
+            let mut output = Vec::new();
+            decompress(&compressed, &mut output).unwrap();
+            assert_eq!(output, input, "steps {steps}");
+        }
+    }
+}
+
+/// Test: A short match is deferred for a longer one starting at the next byte.
+#[test]
+fn t117_lazy_defers_to_longer_match() {
+    // At the last "abcdefghij", greedy takes "abc" then "defghij"; lazy emits 'a'
+    // and then "bcdefghij" as a single match.
+    let input = b"abcbcdefghij-abcdefghij";
+    let mut greedy = Vec::new();
+    compress_with(input, &mut greedy, &CompressOptions::new());
+    let mut lazy = Vec::new();
+    compress_with(input, &mut lazy, &CompressOptions::new().lazy(1));
+    assert!(
+        lazy.len() < greedy.len(),
+        "lazy {} >= greedy {}",
+        lazy.len(),
+        greedy.len()
+    );
+
+    let mut output = Vec::new();
+    decompress(&lazy, &mut output).unwrap();
+    assert_eq!(output, input);
+}
+
+/// Test: The `better` level sits between the default and best levels.
+#[test]
+fn t118_better_level_ratio() {
+    let input = text_like_input(40000);
+    let size = |options: &CompressOptions| {
+        let mut compressed = Vec::new();
+        compress_with(&input, &mut compressed, options);
+        compressed.len()
+    };
+    let better = size(&CompressOptions::better());
+    assert!(better < size(&CompressOptions::new()));
+    assert!(size(&CompressOptions::best()) <= better);
+}
+
+/// Test: The lazy step count is capped at two.
+#[test]
+fn t119_lazy_steps_clamped() {
+    assert_eq!(
+        CompressOptions::new().lazy(5),
+        CompressOptions::new().lazy(2)
+    );
+    assert_eq!(CompressOptions::new().lazy(0), CompressOptions::new());
+}
+
+// --- Fixed Buffer Compression (Tests 120-123) ---
+
+/// Test: `compress_into` produces the same bytes as `compress`.
+#[test]
+fn t120_compress_into_matches_compress() {
+    let mut ctx = Lznt1Context::new();
+    let incompressible: Vec<u8> = (0..5000).map(|i| ((i * i) % 251) as u8).collect();
+
+    for input in [
+        text_like_input(20000),
+        vec![0u8; 9000],
+        incompressible,
+        Vec::new(),
+    ] {
+        let mut expected = Vec::new();
+        compress(&input, &mut expected);
+
+        let mut buffer = vec![0xAA; expected.len() + 10];
+        let written = compress_into(&input, &mut buffer, &mut ctx).unwrap();
+        assert_eq!(&buffer[..written], expected.as_slice());
+        assert!(buffer[written..].iter().all(|&b| b == 0xAA));
+    }
+}
+
+/// Test: `BufferTooSmall` reports the exact size of the compressed data.
+#[test]
+fn t121_compress_into_buffer_too_small() {
+    let input = text_like_input(10000);
+    let mut expected = Vec::new();
+    compress(&input, &mut expected);
+    let mut ctx = Lznt1Context::new();
+
+    for size in [0, 1, expected.len() / 2, expected.len() - 1] {
+        let mut buffer = vec![0u8; size];
+        assert_eq!(
+            compress_into(&input, &mut buffer, &mut ctx),
+            Err(CompressionError::BufferTooSmall {
+                required: expected.len()
+            }),
+            "size {size}"
+        );
+    }
+
+    let mut buffer = vec![0u8; expected.len()];
+    assert_eq!(
+        compress_into(&input, &mut buffer, &mut ctx),
+        Ok(expected.len())
+    );
+}
+
+/// Test: Incompressible chunks are stored raw and need exactly their size plus a header.
+#[test]
+fn t122_compress_into_raw_fallback() {
+    let input: Vec<u8> = (0..200).map(|i| (i * 7) as u8).collect();
+    let mut ctx = Lznt1Context::new();
+
+    let mut buffer = [0u8; 202];
+    assert_eq!(compress_into(&input, &mut buffer, &mut ctx), Ok(202));
+    assert_eq!(parse_header(&buffer), (false, 200));
+    assert_eq!(&buffer[2..], input.as_slice());
+
+    assert_eq!(
+        compress_into(&input, &mut buffer[..201], &mut ctx),
+        Err(CompressionError::BufferTooSmall { required: 202 })
+    );
+}
+
+/// Test: A context can be reused across inputs without affecting the output.
+#[test]
+fn t123_compress_into_context_reuse() {
+    let first = text_like_input(12000);
+    let second = b"abcabdabcabe".repeat(700);
+    let mut ctx = Lznt1Context::new();
+    let mut buffer = vec![0u8; 20000];
+
+    for input in [&first, &second, &first] {
+        let written = compress_into(input, &mut buffer, &mut ctx).unwrap();
+        let mut output = Vec::new();
+        decompress(&buffer[..written], &mut output).unwrap();
+        assert_eq!(&output, input);
+
+        let mut fresh = Vec::new();
+        compress(input, &mut fresh);
+        assert_eq!(&buffer[..written], fresh.as_slice());
+    }
+}
+
+// --- Streaming Writer (Tests 124-127, 152, `std` feature) ---
+
+/// Test: Small writes produce the same stream as `compress`.
+#[cfg(feature = "std")]
+#[test]
+fn t124_encoder_small_writes() {
+    use std::io::Write;
+
+    let mut input = text_like_input(15000);
+    input.extend(vec![0u8; 5000]);
+    input.extend((0..3000).map(|i| ((i * 37) ^ (i >> 3)) as u8));
+
+    let mut encoder = lznt1::Lznt1Encoder::new(Vec::new());
+    let mut rest = input.as_slice();
+    let mut size = 1;
+    while !rest.is_empty() {
+        let n = encoder.write(&rest[..size.min(rest.len())]).unwrap();
+        rest = &rest[n..];
+        size = size % 13 + 1;
+    }
+    let compressed = encoder.finish().unwrap();
+
+    assert_eq!(compressed, compress_to_vec(&input));
+}
+
+/// Test: `finish` appends the end-of-stream marker only when enabled.
+#[cfg(feature = "std")]
+#[test]
+fn t125_encoder_end_marker() {
+    use std::io::Write;
+
+    let input = text_like_input(6000);
+    let expected = compress_to_vec(&input);
+
+    let mut encoder = lznt1::Lznt1Encoder::new(Vec::new()).end_marker(true);
+    encoder.write_all(&input).unwrap();
+    let compressed = encoder.finish().unwrap();
+    assert_eq!(&compressed[..expected.len()], expected.as_slice());
+    assert_eq!(&compressed[expected.len()..], &[0, 0]);
+
+    let mut output = Vec::new();
+    let status =
+        decompress_with_status(&compressed, &mut output, &DecompressOptions::new()).unwrap();
+    assert!(status.terminated);
+    assert_eq!(output, input);
+
+    let empty = lznt1::Lznt1Encoder::new(Vec::new()).end_marker(true);
+    assert_eq!(empty.finish().unwrap(), vec![0, 0]);
+    assert!(
+        lznt1::Lznt1Encoder::new(Vec::new())
+            .finish()
+            .unwrap()
+            .is_empty()
+    );
+}
+
+/// Test: `flush` writes complete chunks but keeps a partial chunk buffered.
+#[cfg(feature = "std")]
+#[test]
+fn t126_encoder_flush_complete_chunks() {
+    use std::io::Write;
+
+    let input = text_like_input(5000);
+    let first_chunk = compress_to_vec(&input[..4096]);
+
+    let mut encoder = lznt1::Lznt1Encoder::new(Vec::new());
+    encoder.write_all(&input[..100]).unwrap();
+    encoder.flush().unwrap();
+    assert!(encoder.get_ref().is_empty());
+
+    encoder.write_all(&input[100..4096]).unwrap();
+    encoder.flush().unwrap();
+    assert_eq!(encoder.get_ref(), &first_chunk);
+
+    encoder.write_all(&input[4096..]).unwrap();
+    encoder.flush().unwrap();
+    assert_eq!(encoder.get_ref(), &first_chunk);
+
+    assert_eq!(encoder.finish().unwrap(), compress_to_vec(&input));
+}
+
+/// Test: The encoder honors compression options.
+#[cfg(feature = "std")]
+#[test]
+fn t127_encoder_with_options() {
+    use std::io::Write;
+
+    let input = text_like_input(12000);
+    for options in [CompressOptions::fastest(), CompressOptions::maximum()] {
+        let mut expected = Vec::new();
+        compress_with(&input, &mut expected, &options);
+
+        let mut encoder = lznt1::Lznt1Encoder::with_options(Vec::new(), options);
+        encoder.write_all(&input).unwrap();
+        assert_eq!(encoder.finish().unwrap(), expected);
+    }
+}
+
+/// Test: A write retried after the inner writer fails mid-chunk resumes the stream.
+#[cfg(feature = "std")]
+#[test]
+fn t152_encoder_retries_after_inner_error() {
+    use std::io::{self, Write};
+
+    /// Accepts bytes up to `fail_at`, then fails once.
+    struct FailingWriter {
+        data: Vec<u8>,
+        fail_at: Option<usize>,
+    }
+
+    impl Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let n = match self.fail_at {
+                Some(at) if self.data.len() == at => {
+                    self.fail_at = None;
+                    return Err(io::Error::other("disk full"));
+                }
+                Some(at) => buf.len().min(at - self.data.len()),
+                None => buf.len(),
+            };
+            self.data.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let input = text_like_input(10000);
+    let expected = compress_to_vec(&input);
+    // Inside the first header, right after it, and inside the first body.
+    for fail_at in [1, 2, 3, 100] {
+        let writer = FailingWriter {
+            data: Vec::new(),
+            fail_at: Some(fail_at),
+        };
+        let mut encoder = lznt1::Lznt1Encoder::new(writer);
+        encoder.write_all(&input[..4096]).unwrap();
+        assert!(encoder.write(&input[4096..]).is_err());
+        assert_eq!(encoder.get_ref().data.len(), fail_at);
+
+        encoder.write_all(&input[4096..]).unwrap();
+        assert_eq!(encoder.finish().unwrap().data, expected);
+    }
+}
+
+// --- Parallel Compression (Tests 128-131, `rayon` feature) ---
+
+/// Test: Parallel compression is byte-identical to `compress`.
+#[cfg(feature = "rayon")]
+#[test]
+fn t128_par_compress_matches_sequential() {
+    let mut input = text_like_input(200_000);
+    input.extend(vec![0u8; 70_000]);
+    input.extend((0..90_000u64).map(|i| ((i * i) % 251) as u8));
+
+    let mut compressed = Vec::new();
+    lznt1::par_compress(&input, &mut compressed);
+    assert_eq!(compressed, compress_to_vec(&input));
+
+    let mut output = Vec::new();
+    lznt1::par_decompress(&compressed, &mut output).unwrap();
+    assert_eq!(output, input);
+}
+
+/// Test: Parallel compression appends to existing output.
+#[cfg(feature = "rayon")]
+#[test]
+fn t129_par_compress_appends() {
+    let input = text_like_input(100_000);
+    let mut compressed = b"prefix".to_vec();
+    lznt1::par_compress(&input, &mut compressed);
+    assert_eq!(&compressed[..6], b"prefix");
+    assert_eq!(&compressed[6..], compress_to_vec(&input).as_slice());
+}
+
+/// Test: Inputs at and around task boundaries match `compress`.
+#[cfg(feature = "rayon")]
+#[test]
+fn t130_par_compress_boundaries() {
+    let source = text_like_input(4096 * 33 + 1);
+    for len in [
+        0,
+        1,
+        4095,
+        4096,
+        4097,
+        4096 * 16,
+        4096 * 16 + 1,
+        4096 * 32,
+        source.len(),
+    ] {
+        let mut compressed = Vec::new();
+        lznt1::par_compress(&source[..len], &mut compressed);
+        assert_eq!(compressed, compress_to_vec(&source[..len]), "len {len}");
+    }
+}
+
+/// Test: Incompressible chunks are stored raw, as with `compress`.
+#[cfg(feature = "rayon")]
+#[test]
+fn t131_par_compress_raw_chunks() {
+    let mut seed = 0x9E37_79B9u32;
+    let input: Vec<u8> = (0..4096 * 20)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as u8
+        })
+        .collect();
+    let mut compressed = Vec::new();
+    lznt1::par_compress(&input, &mut compressed);
+    assert_eq!(compressed, compress_to_vec(&input));
+    assert_eq!(compressed.len(), input.len() + 20 * 2);
+    for chunk in chunks(&compressed) {
+        assert!(!chunk.unwrap().header.compressed);
+    }
+}
+
+// --- Reusable Compressor (Tests 132-135) ---
+
+/// Test: A compressor reused across inputs matches `compress_with`.
+#[test]
+fn t132_compressor_matches_compress_with() {
+    let inputs = [
+        text_like_input(9000),
+        b"abcabdabcabe".repeat(50),
+        Vec::new(),
+        vec![7u8; 4097],
+    ];
+    for options in [
+        CompressOptions::new(),
+        CompressOptions::fastest(),
+        CompressOptions::maximum(),
+    ] {
+        let mut compressor = Compressor::with_options(options);
+        assert_eq!(compressor.options(), &options);
+        for input in inputs.iter().chain(&inputs) {
+            let mut expected = Vec::new();
+            compress_with(input, &mut expected, &options);
+            let mut compressed = Vec::new();
+            compressor.compress(input, &mut compressed);
+            assert_eq!(compressed, expected);
+        }
+    }
+}
+
+/// Test: Inline and borrowed contexts behave like the boxed default.
+#[test]
+fn t133_compressor_context_storage() {
+    let input = text_like_input(10000);
+    let expected = compress_to_vec(&input);
+
+    let mut inline = Compressor::with_context(Lznt1Context::new(), CompressOptions::new());
+    let mut compressed = Vec::new();
+    inline.compress(&input, &mut compressed);
+    assert_eq!(compressed, expected);
+
+    let mut ctx = Lznt1Context::new();
+    let mut borrowed = Compressor::with_context(&mut ctx, CompressOptions::new());
+    let mut buffer = vec![0u8; expected.len()];
+    assert_eq!(
+        borrowed.compress_into(&input, &mut buffer),
+        Ok(expected.len())
+    );
+    assert_eq!(buffer, expected);
+    assert_eq!(
+        borrowed.compress_into(&input, &mut buffer[..10]),
+        Err(CompressionError::BufferTooSmall {
+            required: expected.len()
+        })
+    );
+}
+
+/// Test: The workspace size covers the context and grows with the optimal parse.
+#[test]
+fn t134_compressor_workspace_size() {
+    let default = Compressor::workspace_size(&CompressOptions::new());
+    assert!(default >= std::mem::size_of::<Lznt1Context>() + 4096);
+    assert_eq!(
+        Compressor::workspace_size(&CompressOptions::fastest()),
+        default
+    );
+    assert_eq!(
+        Compressor::workspace_size(&CompressOptions::best()),
+        default
+    );
+    assert!(Compressor::workspace_size(&CompressOptions::maximum()) > default + 4096);
+}
+
+/// Test: A boxed compressor runs on a thread with a small stack.
+#[test]
+fn t135_compressor_small_stack() {
+    let input = text_like_input(50000);
+    let expected = compress_to_vec(&input);
+
+    let compressed = std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(move || {
+            let mut compressor = Box::new(Compressor::new());
+            let mut compressed = Vec::new();
+            compressor.compress(&input, &mut compressed);
+            compressed
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(compressed, expected);
+}
+
+// --- Compression Report (Tests 136-139, 150) ---
+
+/// Test: The report describes the same bytes as `compress_with`, chunk by chunk.
+#[test]
+fn t136_report_matches_output() {
+    let mut input = text_like_input(10000);
+    input.extend(0..=255u8);
+    input.extend(vec![0u8; 3000]);
+
+    for options in [CompressOptions::new(), CompressOptions::maximum()] {
+        let mut expected = Vec::new();
+        compress_with(&input, &mut expected, &options);
+        let mut compressed = Vec::new();
+        let report = compress_with_report(&input, &mut compressed, &options);
+        assert_eq!(compressed, expected);
+        assert_eq!(report.input_len, input.len());
+        assert_eq!(report.output_len, compressed.len());
+
+        let stream: Vec<_> = chunks(&compressed).map(Result::unwrap).collect();
+        assert_eq!(report.chunks.len(), stream.len());
+        let (mut input_end, mut output_end) = (0, 0);
+        for (chunk, parsed) in report.chunks.iter().zip(&stream) {
+            assert_eq!(chunk.input_range.start, input_end);
+            assert_eq!(
+                chunk.output_range,
+                parsed.offset..parsed.offset + 2 + parsed.body.len()
+            );
+            assert_eq!(chunk.raw, !parsed.header.compressed);
+            input_end = chunk.input_range.end;
+            output_end = chunk.output_range.end;
+        }
+        assert_eq!((input_end, output_end), (input.len(), compressed.len()));
+    }
+}
+
+/// Test: Literals and tuples of each compressed chunk account for all its bytes.
+#[test]
+fn t137_report_item_counts() {
+    let input = text_like_input(20000);
+    let mut compressed = Vec::new();
+    let report = compress_with_report(&input, &mut compressed, &CompressOptions::new());
+
+    let mut totals = (0, 0, 0, 0);
+    for chunk in &report.chunks {
+        assert!(!chunk.raw);
+        let stats = chunk.stats;
+        assert_eq!(stats.literals + stats.match_bytes, chunk.input_range.len());
+        assert!(stats.max_offset >= 1 && stats.max_offset <= chunk.input_range.len());
+        assert!(stats.average_match_len() >= 3.0);
+        totals.0 += stats.literals;
+        totals.1 += stats.tuples;
+        totals.2 += stats.match_bytes;
+        totals.3 = totals.3.max(stats.max_offset);
+    }
+    let t = report.totals;
+    assert_eq!((t.literals, t.tuples, t.match_bytes, t.max_offset), totals);
+    assert_eq!(report.raw_chunks, 0);
+    assert!(report.ratio() < 0.5);
+}
+
+/// Test: Exact statistics for a periodic chunk followed by a raw chunk.
+#[test]
+fn t138_report_known_chunks() {
+    let mut input = b"abcd".repeat(1024);
+    input.extend(0..=255u8);
+    let mut compressed = Vec::new();
+    let report = compress_with_report(&input, &mut compressed, &CompressOptions::new());
+
+    let first = &report.chunks[0];
+    assert!(!first.raw);
+    assert_eq!(first.stats.literals, 4);
+    assert_eq!(first.stats.match_bytes, 4092);
+    assert_eq!(first.stats.max_offset, 4);
+
+    let second = &report.chunks[1];
+    assert!(second.raw);
+    assert_eq!(second.input_range, 4096..4352);
+    assert_eq!(second.output_range.len(), 258);
+    assert_eq!(second.stats, lznt1::MatchStats::default());
+    assert_eq!(report.raw_chunks, 1);
+}
+
+/// Test: Ranges are relative to the appended data, and empty input yields no chunk.
+#[test]
+fn t139_report_appends() {
+    let input = text_like_input(5000);
+    let mut compressed = b"prefix".to_vec();
+    let report = compress_with_report(&input, &mut compressed, &CompressOptions::new());
+    assert_eq!(report.chunks[0].output_range.start, 0);
+    assert_eq!(report.output_len, compressed.len() - 6);
+    assert_eq!(&compressed[6..], compress_to_vec(&input).as_slice());
+
+    let mut empty = Vec::new();
+    let report = compress_with_report(&[], &mut empty, &CompressOptions::new());
+    assert!(report.chunks.is_empty() && empty.is_empty());
+    assert_eq!(report.ratio(), 0.0);
+}
+
+/// Test: Every encoding path counts the items it emits, including the optimal parser.
+#[test]
+fn t150_report_counts_every_path() {
+    let mut input = vec![0u8; 4096];
+    input.extend(text_like_input(8000));
+    input.extend(vec![7u8; 600]);
+
+    for options in [
+        CompressOptions::fastest(),
+        CompressOptions::new(),
+        CompressOptions::maximum(),
+    ] {
+        let mut compressed = Vec::new();
+        let report = compress_with_report(&input, &mut compressed, &options);
+
+        let uniform = report.chunks[0].stats;
+        assert_eq!((uniform.literals, uniform.tuples), (1, 1));
+        assert_eq!((uniform.match_bytes, uniform.max_offset), (4095, 1));
+        for chunk in &report.chunks {
+            assert!(!chunk.raw);
+            let stats = chunk.stats;
+            assert_eq!(stats.literals + stats.match_bytes, chunk.input_range.len());
+            // Each literal takes a byte and each tuple two, plus one tag byte per 8 items.
+            let items = stats.literals + stats.tuples;
+            let body_len = stats.literals + 2 * stats.tuples + items.div_ceil(8);
+            assert_eq!(chunk.output_range.len(), 2 + body_len);
+        }
+    }
+}
+
+// --- Run Fast Paths (Tests 140-143) ---
+
+/// Test: A zero-filled chunk is a literal followed by a single offset-1 tuple.
+#[test]
+fn t140_zero_chunk_encoding() {
+    let compressed = compress_to_vec(&vec![0u8; 4096 * 3]);
+    // Header (6-byte chunk), tag 0b10, literal 0, tuple (offset 1, length 4095).
+    let chunk = [0x03, 0xB0, 0x02, 0x00, 0xFC, 0x0F];
+    assert_eq!(compressed, chunk.repeat(3));
+}
+
+/// Test: Uniform chunks get the same encoding at every level, down to the
+/// shortest chunk worth compressing.
+#[test]
+fn t141_uniform_chunks_all_levels() {
+    for options in [
+        CompressOptions::fastest(),
+        CompressOptions::new(),
+        CompressOptions::maximum(),
+    ] {
+        for len in [1, 2, 3, 4, 5, 6, 100, 4095, 4096] {
+            let input = vec![0xEEu8; len];
+            let mut compressed = Vec::new();
+            compress_with(&input, &mut compressed, &options);
+            let (is_compressed, body_len) = parse_header(&compressed);
+            assert_eq!(is_compressed, len >= 5, "len {len}");
+            assert_eq!(body_len, if len >= 5 { 4 } else { len });
+
+            let mut output = Vec::new();
+            decompress(&compressed, &mut output).unwrap();
+            assert_eq!(output, input);
+        }
+    }
+}
+
+/// Test: Long runs inside mixed chunks round-trip wherever the split narrows.
+#[test]
+fn t142_runs_in_mixed_chunks() {
+    let text = text_like_input(9000);
+    let mut input = Vec::new();
+    for (i, run) in [31, 32, 33, 100, 700, 1500, 3000, 5000]
+        .into_iter()
+        .enumerate()
+    {
+        input.extend_from_slice(&text[i * 500..i * 500 + 37 * (i + 1)]);
+        input.extend(vec![b'a' + i as u8; run]);
+    }
+
+    for options in [
+        CompressOptions::fastest(),
+        CompressOptions::new(),
+        CompressOptions::maximum(),
+    ] {
+        let mut compressed = Vec::new();
+        compress_with(&input, &mut compressed, &options);
+        assert!(compressed.len() < input.len() / 8);
+        let mut output = Vec::new();
+        decompress(&compressed, &mut output).unwrap();
+        assert_eq!(output, input);
+    }
+}
+
+/// Test: Runs are encoded as offset-1 tuples clamped to the split at each position.
+#[test]
+fn t143_run_tuples_respect_split() {
+    let mut input = text_like_input(2100);
+    input.extend(vec![0u8; 1996]);
+
+    let mut compressed = Vec::new();
+    let report = compress_with_report(&input, &mut compressed, &CompressOptions::new());
+    let stats = report.chunks[0].stats;
+    assert_eq!(stats.literals + stats.match_bytes, 4096);
+
+    let mut output = Vec::new();
+    decompress(&compressed, &mut output).unwrap();
+    assert_eq!(output, input);
+
+    // From position 2049 the split allows tuples of at most 18 bytes, so a run of
+    // 1995 bytes needs at least 111 tuples.
+    assert!(stats.tuples >= 111);
+    assert!(compressed.len() < input.len() / 2);
+}
+
+// --- Output Framing (Tests 144-147) ---
+
+/// Test: The end-of-stream marker is appended by every entry point when enabled.
+#[test]
+fn t144_end_marker_option() {
+    let input = text_like_input(9000);
+    let plain = compress_to_vec(&input);
+    let options = CompressOptions::new().end_marker(true);
+    let mut expected = plain.clone();
+    expected.extend_from_slice(&[0, 0]);
+
+    let mut compressed = Vec::new();
+    compress_with(&input, &mut compressed, &options);
+    assert_eq!(compressed, expected);
+
+    let mut compressed = Vec::new();
+    Compressor::with_options(options).compress(&input, &mut compressed);
+    assert_eq!(compressed, expected);
+
+    let mut compressed = Vec::new();
+    let report = compress_with_report(&input, &mut compressed, &options);
+    assert_eq!(compressed, expected);
+    assert_eq!(report.output_len, expected.len());
+
+    let mut output = Vec::new();
+    let status =
+        decompress_with_status(&compressed, &mut output, &DecompressOptions::new()).unwrap();
+    assert!(status.terminated);
+    assert_eq!(output, input);
+
+    let mut empty = Vec::new();
+    compress_with(&[], &mut empty, &options);
+    assert_eq!(empty, [0, 0]);
+}
+
+/// Test: A chunk is only stored compressed if it saves `min_savings` bytes.
+#[test]
+fn t145_min_savings() {
+    // A uniform chunk of `len` bytes compresses to a 4-byte body.
+    let is_compressed = |len: usize, min_savings: usize| {
+        let mut compressed = Vec::new();
+        let options = CompressOptions::new().min_savings(min_savings);
+        compress_with(&vec![7u8; len], &mut compressed, &options);
+        let mut output = Vec::new();
+        decompress(&compressed, &mut output).unwrap();
+        assert_eq!(output, vec![7u8; len]);
+        parse_header(&compressed).0
+    };
+
+    assert!(!is_compressed(4, 1));
+    assert!(is_compressed(4, 0));
+    assert!(is_compressed(5, 1));
+    assert!(!is_compressed(5, 2));
+    assert!(is_compressed(6, 2));
+    assert!(!is_compressed(4096, 5000));
+}
+
+/// Test: `RawChunks` forces or avoids raw chunks, keeping non-final raw chunks full.
+#[test]
+fn t146_raw_chunk_policy() {
+    let mut input = text_like_input(10000);
+    let incompressible: Vec<u8> = (0..200).map(|i| (i * 7) as u8).collect();
+    input.extend_from_slice(&incompressible);
+
+    let mut compressed = Vec::new();
+    compress_with(
+        &input,
+        &mut compressed,
+        &CompressOptions::new().raw_chunks(RawChunks::Always),
+    );
+    let stream: Vec<_> = chunks(&compressed).map(Result::unwrap).collect();
+    assert_eq!(stream.len(), 3);
+    for chunk in &stream[..2] {
+        assert!(!chunk.header.compressed);
+        assert_eq!(chunk.body.len(), 4096);
+    }
+    assert_eq!(compressed.len(), input.len() + 6);
+
+    let mut compressed = Vec::new();
+    compress_with(
+        &incompressible,
+        &mut compressed,
+        &CompressOptions::new().raw_chunks(RawChunks::Never),
+    );
+    // 200 literals and 25 tag bytes.
+    assert_eq!(parse_header(&compressed), (true, 225));
+    let mut output = Vec::new();
+    decompress(&compressed, &mut output).unwrap();
+    assert_eq!(output, incompressible);
+    let strict = DecompressOptions::new().validation(ValidationLevel::Strict);
+    assert!(matches!(
+        decompress_with(&compressed, &mut Vec::new(), &strict),
+        Err(DecompressionError::CompressedChunkTooLarge { .. })
+    ));
+
+    // A full incompressible chunk cannot fit a compressed body and stays raw.
+    let mut seed = 0x9E37_79B9u32;
+    let random: Vec<u8> = (0..4096)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as u8
+        })
+        .collect();
+    let mut compressed = Vec::new();
+    compress_with(
+        &random,
+        &mut compressed,
+        &CompressOptions::new().raw_chunks(RawChunks::Never),
+    );
+    assert_eq!(parse_header(&compressed), (false, 4096));
+    let mut output = Vec::new();
+    decompress(&compressed, &mut output).unwrap();
+    assert_eq!(output, random);
+}
+
+/// Test: The streaming encoder honors the end marker option and its override.
+#[cfg(feature = "std")]
+#[test]
+fn t147_encoder_end_marker_option() {
+    use std::io::Write;
+
+    let input = text_like_input(5000);
+    let plain = compress_to_vec(&input);
+
+    let mut encoder =
+        lznt1::Lznt1Encoder::with_options(Vec::new(), CompressOptions::new().end_marker(true));
+    encoder.write_all(&input).unwrap();
+    let compressed = encoder.finish().unwrap();
+    assert_eq!(&compressed[..plain.len()], plain.as_slice());
+    assert_eq!(&compressed[plain.len()..], &[0, 0]);
+
+    let mut encoder =
+        lznt1::Lznt1Encoder::with_options(Vec::new(), CompressOptions::new().end_marker(true))
+            .end_marker(false);
+    encoder.write_all(&input).unwrap();
+    assert_eq!(encoder.finish().unwrap(), plain);
+}